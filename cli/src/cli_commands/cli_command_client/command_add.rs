@@ -1,18 +1,46 @@
+use crate::cli_commands::cli_command_client::dry_run::{DryRun, DryRunOperation};
 use crate::cli_commands::client::{exec, NewArgs};
 use crate::data::client_result::ClientResult;
+use crate::data::ignore_rules::IgnoreRules;
 
 pub async fn client_add(args: NewArgs) -> Option<ClientResult> {
     
     // Create result struct
     let mut result = ClientResult::result().await;
 
+    // Bulk add skips ignored files
+    let mut path = args.path.clone();
+    if path.contains("|") {
+        let rules = IgnoreRules::load();
+        let (ignored, kept): (Vec<&str>, Vec<&str>) = args.path.split("|")
+            .partition(|path| rules.is_ignored(path));
+        for ignored_path in ignored {
+            result.warn(format!("Skipped ignored file '{}'", ignored_path).as_str());
+        }
+        if kept.is_empty() {
+            result.warn("Nothing to add.");
+            return Some(result);
+        }
+        path = kept.join("|");
+    }
+
+    // Preview only
+    if args.dry_run {
+        let mut dry_run = DryRun::begin().await;
+        for path in path.split("|") {
+            dry_run.preview(&DryRunOperation::Add { get: args.get }, path, None);
+        }
+        result.combine_unchecked(dry_run.end());
+        return Some(result);
+    }
+
     // Add file
-    result.combine_unchecked(exec(vec!["file".to_string(), "add".to_string(), args.path.clone()]).await);
+    result.combine_unchecked(exec(vec!["file".to_string(), "add".to_string(), path.clone()]).await);
 
     if args.get {
         // Acquire file lock
-        result.combine_unchecked(exec(vec!["file".to_string(), "get".to_string(), args.path]).await);
+        result.combine_unchecked(exec(vec!["file".to_string(), "get".to_string(), path]).await);
     }
 
     Some(result)
-}
\ No newline at end of file
+}
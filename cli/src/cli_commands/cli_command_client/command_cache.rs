@@ -0,0 +1,151 @@
+use crate::cli_commands::client::CacheArgs;
+use crate::data::client_result::ClientResult;
+use crate::data::database::Database;
+use crate::data::local_cache::LocalCache;
+use colored::Colorize;
+use jam_ready::utils::file_digest::md5_digest;
+use jam_ready::utils::local_archive::LocalArchive;
+use std::collections::HashMap;
+
+pub async fn client_cache(args: CacheArgs) -> Option<ClientResult> {
+
+    // Create result struct
+    let mut result = ClientResult::result().await;
+
+    let database = Database::read().await;
+    let mut cache = LocalCache::read().await;
+    cache.scan();
+
+    // Size limit
+    if let Some(limit) = args.limit {
+        cache.limit_mb = limit;
+        result.log(format!("Cache limit set to {}", format_limit(limit)).as_str());
+    }
+
+    // Pin or unpin a version
+    for (search, pinned) in [(&args.pin, true), (&args.unpin, false)] {
+        let Some(search) = search else { continue; };
+        let Some(file) = database.search_file(search.clone()) else {
+            result.err(format!("File '{}' not found", search).as_str());
+            continue;
+        };
+        let version = if args.version == 0 { file.version() } else { args.version };
+        let changed = LocalCache::path_of(file, version)
+            .map(|path| cache.set_pinned(&path, pinned))
+            .unwrap_or(false);
+        if !changed {
+            result.err(format!("{} v{} is not cached, run \"jam view\" first", file.path(), version).as_str());
+        } else if pinned {
+            result.log(format!("Pinned {} v{}", file.path(), version).as_str());
+        } else {
+            result.log(format!("Unpinned {} v{}", file.path(), version).as_str());
+        }
+    }
+
+    // Remove versions older than the latest, and files removed from the server
+    if args.clean {
+        let latest = latest_versions(&database);
+        let old: Vec<String> = cache.entries.iter()
+            .filter(|(key, entry)| !entry.pinned && latest.get(*key).map(|(old, _)| *old).unwrap_or(true))
+            .map(|(key, _)| key.clone())
+            .collect();
+        let removed = old.iter().filter(|key| cache.remove(key)).count();
+        result.log(format!("Removed {} old version(s)", removed).as_str());
+    }
+
+    // Remove everything except pinned versions
+    if args.clear {
+        let unpinned: Vec<String> = cache.entries.iter()
+            .filter(|(_, entry)| !entry.pinned)
+            .map(|(key, _)| key.clone())
+            .collect();
+        let removed = unpinned.iter().filter(|key| cache.remove(key)).count();
+        result.log(format!("Removed {} cached file(s)", removed).as_str());
+    }
+
+    // Check cached files against the digests on the server, broken files are removed
+    if args.verify {
+        let directory = LocalCache::directory()?;
+        let digests = server_digests(&database);
+        let mut broken = Vec::new();
+        let mut unknown = 0;
+        for (key, entry) in &cache.entries {
+
+            // Versions from before digests were recorded fall back to the digest taken when cached
+            let expected = digests.get(key).unwrap_or(&entry.digest);
+            if expected.is_empty() {
+                unknown += 1;
+                continue;
+            }
+            if md5_digest(directory.join(key)).unwrap_or_default() != *expected {
+                broken.push(key.clone());
+            }
+        }
+        for key in &broken {
+            cache.remove(key);
+            result.warn(format!("Removed broken cache file {}", key).as_str());
+        }
+        result.log(format!("Verified {} file(s), {} broken, {} without digest",
+                           cache.entries.len() + broken.len() - unknown, broken.len(), unknown).as_str());
+    }
+
+    cache.evict(None);
+    LocalCache::update(&cache).await;
+
+    // List cached files, most recently used first
+    if args.list {
+        let latest = latest_versions(&database);
+        let mut entries: Vec<_> = cache.entries.iter().collect();
+        entries.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.last_used));
+        for (key, entry) in entries {
+            let name = match latest.get(key) {
+                Some((_, (path, version))) => format!("{} v{}", path, version),
+                None => key.clone(),
+            };
+            let pinned = if entry.pinned { " [pinned]".green().to_string() } else { String::new() };
+            result.log(format!("{} {}{}", name, format_size(entry.size).truecolor(128, 128, 128), pinned).as_str());
+        }
+    }
+
+    // Usage
+    let pinned = cache.entries.values().filter(|entry| entry.pinned).count();
+    result.log(format!("Cache: {} / {}, {} file(s), {} pinned",
+                       format_size(cache.usage()), format_limit(cache.limit_mb),
+                       cache.entries.len(), pinned).as_str());
+
+    Some(result)
+}
+
+/// Cache keys of all file versions (Key -> (Is old version, (Path, Version)))
+fn latest_versions(database: &Database) -> HashMap<String, (bool, (String, u32))> {
+    let mut versions = HashMap::new();
+    for file in database.files() {
+        for (version, _) in file.change_histories() {
+            let Some(real) = file.real_path_version(version) else { continue; };
+            let key = real.display().to_string().replace("\\", "/");
+            if key.is_empty() { continue; }
+            versions.insert(key, (version < file.version() || file.path().is_empty(), (file.path(), version)));
+        }
+    }
+    versions
+}
+
+/// Digests of all file versions recorded by the server (Key -> Digest)
+fn server_digests(database: &Database) -> HashMap<String, String> {
+    let mut digests = HashMap::new();
+    for file in database.files() {
+        for (version, _) in file.change_histories() {
+            let (Some(real), Some(digest)) = (file.real_path_version(version), file.version_digest(version)) else { continue; };
+            digests.insert(real.display().to_string().replace("\\", "/"), digest);
+        }
+    }
+    digests
+}
+
+fn format_size(size: u64) -> String {
+    format!("{:.1} MB", size as f64 / 1024.0 / 1024.0)
+}
+
+fn format_limit(limit_mb: u64) -> String {
+    if limit_mb == 0 { "unlimited".to_string() } else { format!("{} MB", limit_mb) }
+}
@@ -1,6 +1,44 @@
-use crate::cli_commands::client::exec;
+use jam_ready::utils::local_archive::LocalArchive;
+use crate::cli_commands::client::{exec, CommitArgs};
 use crate::data::client_result::ClientResult;
+use crate::data::database::Database;
+use crate::data::local_file_map::LocalFileMap;
+use crate::data::workspace::Workspace;
+use crate::service::commands::workspace_status::collect_status;
 
-pub async fn client_commit() -> Option<ClientResult> {
-    exec(vec!["commit".to_string()]).await
+pub async fn client_commit(args: CommitArgs) -> Option<ClientResult> {
+    if args.dry_run {
+        return client_commit_dry_run().await;
+    }
+    if let Some(message) = args.message {
+        exec(vec!["commit".to_string(), message]).await
+    } else {
+        exec(vec!["commit".to_string()]).await
+    }
+}
+
+/// Preview which files would be committed
+async fn client_commit_dry_run() -> Option<ClientResult> {
+    let mut result = ClientResult::result().await;
+
+    // Sync database
+    result.combine_unchecked(exec(vec!["update".to_string()]).await);
+
+    let database = Database::read().await;
+    let local = LocalFileMap::read().await;
+    let self_uuid = Workspace::read().await.client.map(|client| client.uuid).unwrap_or_default();
+    let status = collect_status(&database, &local, self_uuid.trim());
+
+    for path in &status.completed {
+        result.log(format!("Would commit {}", path).as_str());
+    }
+    for path in &status.modified {
+        result.warn(format!("{} is modified but not completed, skipped", path).as_str());
+    }
+    for path in &status.modified_unlocked {
+        result.warn(format!("{} is modified but not held, skipped", path).as_str());
+    }
+    result.log(format!("{} file(s) would be committed. Nothing was changed.", status.completed.len()).as_str());
+
+    Some(result)
 }
\ No newline at end of file
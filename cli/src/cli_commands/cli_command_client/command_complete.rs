@@ -35,6 +35,10 @@ pub async fn client_complete(args: CompleteArgs) -> Option<ClientResult> {
         let Some(local_file) = local.search_to_local_mut(&database, final_path.clone()) else { continue; };
         let Ok(digest) = md5_digest(path) else { continue; };
         if digest == local_file.local_digest { continue; };
+        if args.dry_run {
+            result.log(format!("Would complete {}", local_file.local_path).as_str());
+            continue;
+        }
         local_file.completed = true;
         local_file.completed_commit = commit.clone();
         local_file.completed_digest = digest;
@@ -42,7 +46,11 @@ pub async fn client_complete(args: CompleteArgs) -> Option<ClientResult> {
     }
 
     // Update local database
-    let _ = LocalFileMap::update(&local).await;
+    if args.dry_run {
+        result.log("Nothing was changed.");
+    } else {
+        let _ = LocalFileMap::update(&local).await;
+    }
 
     // No results
     if result.has_result() {
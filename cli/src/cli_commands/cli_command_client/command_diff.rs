@@ -0,0 +1,34 @@
+use crate::cli_commands::cli_command_client::param_comp::comp::comp_param_from;
+use crate::cli_commands::cli_command_client::param_comp::data::{CompConfig, CompContext};
+use crate::cli_commands::client::{exec, DiffArgs};
+use crate::data::client_result::ClientResult;
+
+pub async fn client_diff(args: DiffArgs) -> Option<ClientResult> {
+
+    let config = CompConfig::read().await;
+    let from = comp_param_from(&config, CompContext::input(&args.from_search));
+    let Ok(from) = from else {
+        ClientResult::result().await.err_and_end(format!("{}", from.err().unwrap()).as_str());
+        return None;
+    };
+
+    // Old side: the given version, or the latest version ("0")
+    let old_version = args.version.unwrap_or(0).to_string();
+
+    // New side: the latest server version, or the local file
+    let new_version = if args.remote {
+        if args.version.is_none() {
+            ClientResult::result().await.err_and_end("Please specify --version to compare with the latest version");
+            return None;
+        }
+        "0".to_string()
+    } else {
+        "local".to_string()
+    };
+
+    let mut command = vec!["diff".to_string(), from.to_string(), old_version, new_version];
+    if args.json {
+        command.push("json".to_string());
+    }
+    exec(command).await
+}
@@ -1,3 +1,4 @@
+use crate::cli_commands::cli_command_client::dry_run::{client_dry_run, DryRunOperation};
 use crate::cli_commands::cli_command_client::param_comp::comp::comp_param_from;
 use crate::cli_commands::cli_command_client::param_comp::data::{CompConfig, CompContext};
 use crate::cli_commands::client::{exec, GetArgs};
@@ -5,6 +6,11 @@ use crate::data::client_result::ClientResult;
 
 pub async fn client_get (args: GetArgs) -> Option<ClientResult> {
 
+    // Preview only
+    if args.dry_run {
+        return client_dry_run(DryRunOperation::Get { longer: args.longer }, &args.from_search, None).await;
+    }
+
     // Create result struct
     let mut result = ClientResult::result().await;
 
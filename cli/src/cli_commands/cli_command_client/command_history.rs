@@ -0,0 +1,77 @@
+use chrono::{Local, TimeZone};
+use colored::Colorize;
+use jam_ready::utils::local_archive::LocalArchive;
+use crate::cli_commands::cli_command_client::param_comp::comp::comp_param_from;
+use crate::cli_commands::cli_command_client::param_comp::data::{CompConfig, CompContext};
+use crate::cli_commands::client::HistoryArgs;
+use crate::data::client_result::{ClientResult, ClientResultQueryProcess};
+use crate::data::database::{Changeset, Database};
+
+pub async fn client_history(args: HistoryArgs) -> Option<ClientResult> {
+
+    // Create query result
+    let mut result = ClientResult::query(ClientResultQueryProcess::line_by_line).await;
+    let database = Database::read().await;
+
+    // Show single changeset
+    if let Some(id) = args.changeset {
+        let Some(changeset) = database.changeset(&id) else {
+            result.err(format!("Changeset '{}' not found", id.trim()).as_str());
+            return Some(result);
+        };
+        result.log(changeset_line(id.trim(), changeset).as_str());
+        for (uuid, (before, after)) in &changeset.files {
+            let path = database.file_with_uuid(uuid.clone())
+                .map(|file| file.path())
+                .filter(|path| !path.is_empty())
+                .unwrap_or(uuid.clone());
+            result.log(format!("    {} v{} -> v{}", path, before, after).as_str());
+        }
+        return Some(result);
+    }
+
+    // Show file histories
+    if let Some(from_search) = args.from_search {
+        let config = CompConfig::read().await;
+        let from = comp_param_from(&config, CompContext::input(&from_search));
+        let Ok(from) = from else {
+            result.err_and_end(format!("{}", from.err().unwrap()).as_str());
+            return None;
+        };
+
+        for final_path in from.final_paths {
+            let Some(file) = database.search_file(final_path.clone()) else { continue; };
+            let uuid = database.uuid_of_path(file.path()).unwrap_or_default();
+            result.log(file.path().cyan().to_string().as_str());
+            for (version, info) in file.change_histories() {
+
+                // Find the changeset that produced this version
+                let changeset_id = database.changesets().into_iter()
+                    .rev()
+                    .find(|(_, changeset)| changeset.files.get(&uuid).map(|(_, after)| *after) == Some(version))
+                    .map(|(id, _)| format!(" ({})", id))
+                    .unwrap_or_default();
+
+                let current = if version == file.version() { "*" } else { " " };
+                result.log(format!("  {} v{} {}{}", current, version, info, changeset_id.truecolor(128, 128, 128)).as_str());
+            }
+        }
+        return Some(result);
+    }
+
+    // Show all changesets, newest first
+    for (id, changeset) in database.changesets().into_iter().rev() {
+        result.log(changeset_line(id, changeset).as_str());
+    }
+    Some(result)
+}
+
+fn changeset_line(id: &str, changeset: &Changeset) -> String {
+    let time = Local.timestamp_opt(changeset.time as i64, 0)
+        .single()
+        .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    let reverted = if changeset.reverted { " [Reverted]".red().to_string() } else { String::new() };
+    format!("{} {} {} \"{}\" ({} files){}",
+            id.yellow(), time, changeset.member.green(), changeset.message, changeset.files.len(), reverted)
+}
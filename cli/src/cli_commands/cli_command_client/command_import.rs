@@ -0,0 +1,160 @@
+use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
+use jam_ready::utils::file_digest::md5_digest;
+use jam_ready::utils::local_archive::LocalArchive;
+use jam_ready::utils::text_process::process_path_text;
+use std::env::current_dir;
+use walkdir::WalkDir;
+use crate::cli_commands::client::{exec, ImportArgs};
+use crate::data::client_result::ClientResult;
+use crate::data::database::Database;
+use crate::data::ignore_rules::IgnoreRules;
+use crate::data::local_file_map::LocalFileMap;
+
+/// Skipped paths listed in the summary
+const MAX_LISTED_SKIPPED: usize = 10;
+
+pub async fn client_import(args: ImportArgs) -> Option<ClientResult> {
+
+    // Create result struct
+    let mut result = ClientResult::result().await;
+
+    let Ok(current) = current_dir() else { return None; };
+    let directory = current.join(&args.directory);
+    if !directory.is_dir() {
+        result.err_and_end(format!("Directory '{}' not found", args.directory).as_str());
+        return None;
+    }
+    if !directory.starts_with(&current) {
+        result.err_and_end("The directory must be inside the workspace");
+        return None;
+    }
+
+    // Preview against a freshly synced database
+    if args.dry_run {
+        result.combine_unchecked(exec(vec!["update".to_string()]).await);
+    }
+
+    let database = Database::read().await;
+    let local = LocalFileMap::read().await;
+    let rules = IgnoreRules::load();
+
+    // Scan directory, ignored directories are skipped as a whole
+    let mut candidates = Vec::new();
+    let mut ignored = Vec::new();
+    let mut tracked = Vec::new();
+    let mut empty = Vec::new();
+    let mut ignored_dirs = Vec::new();
+    let walker = WalkDir::new(&directory).into_iter().filter_entry(|entry| {
+        if !entry.file_type().is_dir() { return true; }
+        let Ok(relative) = entry.path().strip_prefix(&current) else { return true; };
+        let path = process_path_text(relative.display().to_string());
+        if !path.is_empty() && rules.is_ignored_dir(&path) {
+            ignored_dirs.push(format!("{}/", path));
+            return false;
+        }
+        true
+    });
+    for entry in walker.filter_map(Result::ok) {
+        if !entry.file_type().is_file() { continue; }
+        let Ok(relative) = entry.path().strip_prefix(&current) else { continue; };
+        let path = process_path_text(relative.display().to_string());
+
+        if rules.is_ignored(&path) {
+            ignored.push(path);
+        } else if database.contains_path(&path) || local.file_uuids.contains_key(&path) {
+            tracked.push(path);
+        } else if entry.metadata().map(|metadata| metadata.len() == 0).unwrap_or(true) {
+            // Empty files cannot be uploaded
+            empty.push(path);
+        } else {
+            candidates.push(path);
+        }
+    }
+    ignored.extend(ignored_dirs);
+
+    if candidates.is_empty() {
+        result.warn("Nothing to import.");
+        print_skipped(&mut result, &ignored, &tracked, &empty);
+        return Some(result);
+    }
+
+    // Preview only
+    if args.dry_run {
+        result.log(format!("Would import {} file(s):", candidates.len()).as_str());
+        for path in &candidates {
+            result.log(format!("    {}", path).as_str());
+        }
+        print_skipped(&mut result, &ignored, &tracked, &empty);
+        result.log("Nothing was changed.");
+        return Some(result);
+    }
+
+    let joined = candidates.join("|");
+
+    // Create all virtual files in one request
+    result.combine_unchecked(exec(vec!["file".to_string(), "add".to_string(), joined.clone()]).await);
+
+    // Lock them for the first commit
+    result.combine_unchecked(exec(vec!["file".to_string(), "get".to_string(), joined.clone()]).await);
+
+    // Mark the new files as completed
+    let message = args.message.unwrap_or(format!("Import {}", process_path_text(args.directory)));
+    let database = Database::read().await;
+    let mut local = LocalFileMap::read().await;
+    let progress_bar = if ClientResult::debug_mode().await {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(candidates.len() as u64)
+    };
+    progress_bar.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.blue} [{bar:40.cyan/blue}] {pos}/{len} {msg}")
+            .unwrap()
+            .progress_chars("■■■")
+    );
+    for path in &candidates {
+        progress_bar.set_message(path.clone());
+        if let Some(local_file) = local.search_to_local_mut(&database, path.clone()) {
+            local_file.completed = true;
+            local_file.completed_digest = md5_digest(current.join(path)).unwrap_or_default();
+            local_file.completed_commit = message.clone();
+        }
+        progress_bar.inc(1);
+    }
+    progress_bar.finish_and_clear();
+    LocalFileMap::update(&local).await;
+
+    // Upload first versions as one changeset
+    result.combine_unchecked(exec(vec!["commit".to_string(), message, joined]).await);
+
+    // Summary from the commit result, the changeset is committed as a whole or not at all
+    let imported = result.get_metadata("committed")
+        .and_then(|count| count.parse::<usize>().ok())
+        .unwrap_or(0)
+        .min(candidates.len());
+    let failed = candidates.len() - imported;
+    let skipped = ignored.len() + tracked.len() + empty.len();
+    let summary = format!("Imported {} file(s), skipped {}, failed {}.", imported, skipped, failed);
+    if failed == 0 {
+        result.log(summary.as_str());
+    } else {
+        result.err(summary.as_str());
+    }
+    print_skipped(&mut result, &ignored, &tracked, &empty);
+    Some(result)
+}
+
+/// Summary of skipped files
+fn print_skipped(result: &mut ClientResult, ignored: &Vec<String>, tracked: &Vec<String>, empty: &Vec<String>) {
+    for (reason, paths) in [("ignored", ignored), ("already tracked", tracked), ("empty", empty)] {
+        if paths.is_empty() { continue; }
+        result.log(format!("Skipped {} file(s), {}:", paths.len(), reason).as_str());
+        for path in paths.iter().take(MAX_LISTED_SKIPPED) {
+            result.log(format!("    {}", path.truecolor(128, 128, 128)).as_str());
+        }
+        if paths.len() > MAX_LISTED_SKIPPED {
+            result.log(format!("    ... {} more", paths.len() - MAX_LISTED_SKIPPED).as_str());
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use crate::cli_commands::cli_command_client::dry_run::{client_dry_run, DryRunOperation};
+use crate::cli_commands::cli_command_client::param_comp::comp::comp_param_from;
+use crate::cli_commands::cli_command_client::param_comp::data::{CompConfig, CompContext};
+use crate::cli_commands::client::{exec, MergeableArgs};
+use crate::data::client_result::ClientResult;
+
+pub async fn client_mergeable(args: MergeableArgs) -> Option<ClientResult> {
+
+    // Preview only
+    if args.dry_run {
+        return client_dry_run(DryRunOperation::Mergeable { on: !args.off }, &args.from_search, None).await;
+    }
+
+    // Create result struct
+    let mut result = ClientResult::result().await;
+
+    // Create compile config
+    let config = CompConfig::read().await;
+
+    // Compile FROM input
+    let from = comp_param_from(&config, CompContext::input(&args.from_search));
+    let Ok(from) = from else {
+        result.err_and_end(format!("{}", from.err().unwrap()).as_str());
+        return None;
+    };
+
+    // Exec mergeable command
+    let operation = if args.off { "unmergeable" } else { "mergeable" };
+    result.combine_unchecked(exec(vec!["file".to_string(), operation.to_string(), from.to_string()]).await);
+
+    Some(result)
+}
@@ -6,11 +6,17 @@ use jam_ready::utils::file_operation::move_file;
 use jam_ready::utils::local_archive::LocalArchive;
 use jam_ready::utils::text_process::process_path_text;
 use std::env::current_dir;
+use crate::cli_commands::cli_command_client::dry_run::{client_dry_run, DryRunOperation};
 use crate::cli_commands::cli_command_client::param_comp::comp::{comp_param_from, comp_param_to};
 use crate::cli_commands::cli_command_client::param_comp::data::{CompConfig, CompContext};
 
 pub async fn client_move(args: MoveArgs) -> Option<ClientResult> {
 
+    // Preview only (local moves are previewed by client_move_local_file)
+    if args.dry_run && !args.local {
+        return client_dry_run(DryRunOperation::Move { get: args.get }, &args.from_search, Some(args.to_search.as_str())).await;
+    }
+
     // Create result struct
     let mut result = ClientResult::result().await;
 
@@ -32,7 +38,7 @@ pub async fn client_move(args: MoveArgs) -> Option<ClientResult> {
     };
 
     // Acquire file lock if requested
-    if args.get {
+    if args.get && !args.dry_run {
         result.combine_unchecked(exec(vec!["file".to_string(), "get".to_string(), from.to_string()]).await);
     }
 
@@ -72,6 +78,12 @@ async fn client_move_local_file(mv: MoveArgs) -> Option<ClientResult> {
     let target_path = current_dir.join(mv.to_search.clone());
 
     if !target_path.exists() {
+        // Preview only
+        if mv.dry_run {
+            result.log(format!("Would move local file '{}' to '{}'. Nothing was changed.", raw_path.display(), target_path.display()).as_str());
+            return Some(result);
+        }
+
         // Update local file path
         let new_path = process_path_text(mv.to_search);
         let old_path = local_file_mut.local_path.clone();
@@ -5,10 +5,7 @@ use crate::data::parameters::{erase_parameter, parameters, read_parameter, write
 pub async fn client_param(args: ParamArgs) -> Option<ClientResult> {
     if let Some(key) = args.key {
         match args.value {
-            None => {
-                client_query_param(key);
-                None
-            },
+            None => client_query_param(key).await,
             Some(content) => if content.trim() == "null" || content.trim() == "none" {
                 erase_parameter(key);
                 None
@@ -38,6 +35,8 @@ pub async fn client_param(args: ParamArgs) -> Option<ClientResult> {
     }
 }
 
-fn client_query_param(param_name: String) {
-    print!("{}", read_parameter(param_name.clone()).unwrap_or("".to_string()));
+async fn client_query_param(param_name: String) -> Option<ClientResult> {
+    let mut result = ClientResult::query(ClientResultQueryProcess::direct).await;
+    result.log(read_parameter(param_name).unwrap_or("".to_string()).as_str());
+    Some(result)
 }
\ No newline at end of file
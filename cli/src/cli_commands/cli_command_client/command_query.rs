@@ -7,6 +7,7 @@ use crate::cli_commands::cli_command_client::param_comp::data::{CompConfig, Comp
 use crate::cli_commands::client::ClientQueryCommands;
 use crate::data::client_result::{ClientResult, ClientResultQueryProcess};
 use crate::data::database::Database;
+use crate::data::ignore_rules::IgnoreRules;
 use crate::data::local_file_map::LocalFileMap;
 use crate::data::local_folder_map::{LocalFolderMap, Node};
 use crate::data::workspace::Workspace;
@@ -18,68 +19,9 @@ pub async fn client_query(command: ClientQueryCommands) -> Option<ClientResult>
         ClientQueryCommands::ListDirectory(args) => {
             let mut result = ClientResult::query(ClientResultQueryProcess::line_by_line).await;
             if args.completion_mode { result.set_debug(false); }
-            let folder_map = LocalFolderMap::read().await;
-            let database = Database::read().await;
-            let current = args.value
-                .trim()
-                .trim_start_matches("./")
-                .trim_start_matches("/");
-
-            // Local files
-            if let Ok(current_dir) = current_dir() {
-                let current_folder = current_dir.join(current);
-                if current_folder.exists() {
-                    if let Ok(dir) = current_folder.read_dir() {
-                        for dir in dir.into_iter() {
-                            if dir.is_err() { continue; }
-                            let dir = dir.unwrap().path();
-                            if let Some(os_name) = dir.file_name() {
-                                if let Some(name) = os_name.to_str() {
-                                    let mut path = format!("{}{}", current, name);
-                                    if dir.is_dir() {
-                                        path = format!("{}/", path);
-                                        if path == env!("PATH_WORKSPACE_ROOT") { continue }
-                                        if ! folder_map.folder_files.contains_key(&path) {
-                                            result.log(format!("{}/", name).as_str());
-                                        }
-                                    } else {
-                                        if ! database.contains_path(&path) {
-                                            result.log(format!("{}", name).as_str());
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+            for item in list_directory(&args.value, args.completion_mode).await {
+                result.log(item.as_str());
             }
-
-            // Remote files
-            let list = folder_map.folder_files.get(current);
-            if let Some(list) = list {
-                for item in list {
-                    match item {
-                        Node::Jump(directory_str) => {
-                            let v = process_path(directory_str.trim().trim_end_matches('/'))
-                                .to_string().add("/");
-                            result.log(v.as_str());
-                        }
-                        Node::File(virtual_file_path_str) => {
-                            let v = process_path(virtual_file_path_str);
-                            result.log(v.as_str());
-                        }
-                        _ => { continue; }
-                    }
-                }
-            }
-
-            // Short names
-            if args.completion_mode {
-                for (k, _v) in folder_map.short_file_map {
-                    result.log(format!(":{}", k).as_str());
-                }
-            }
-
             Some(result)
         }
 
@@ -298,9 +240,76 @@ pub async fn client_query(command: ClientQueryCommands) -> Option<ClientResult>
             return Some(result)
         }
     };
+}
+
+fn process_path(input: &str) -> String {
+    let binding = input.to_string();
+    binding.split("/").last().unwrap_or("").to_string()
+}
+
+/// List a directory (names of remote files, untracked local files and subdirectories)
+/// Completion mode also lists short names (":name")
+pub async fn list_directory(value: &str, completion_mode: bool) -> Vec<String> {
+    let mut result = Vec::new();
+    let folder_map = LocalFolderMap::read().await;
+    let database = Database::read().await;
+    let ignore_rules = IgnoreRules::load();
+    let current = value
+        .trim()
+        .trim_start_matches("./")
+        .trim_start_matches("/");
 
-    fn process_path(input: &str) -> String {
-        let binding = input.to_string();
-        binding.split("/").last().unwrap_or("").to_string()
+    // Local files
+    if let Ok(current_dir) = current_dir() {
+        let current_folder = current_dir.join(current);
+        if current_folder.exists() && let Ok(dir) = current_folder.read_dir() {
+            for dir in dir.into_iter() {
+                if dir.is_err() { continue; }
+                let dir = dir.unwrap().path();
+                if let Some(os_name) = dir.file_name() && let Some(name) = os_name.to_str() {
+                    let mut path = format!("{}{}", current, name);
+                    if dir.is_dir() {
+                        path = format!("{}/", path);
+                        if path == env!("PATH_WORKSPACE_ROOT") { continue }
+                        if ignore_rules.is_ignored_dir(&path) { continue }
+                        if ! folder_map.folder_files.contains_key(&path) {
+                            result.push(format!("{}/", name));
+                        }
+                    } else {
+                        if ! database.contains_path(&path) && ! ignore_rules.is_ignored(&path) {
+                            result.push(name.to_string());
+                        }
+                    }
+                }
+            }
+        }
     }
+
+    // Remote files
+    let list = folder_map.folder_files.get(current);
+    if let Some(list) = list {
+        for item in list {
+            match item {
+                Node::Jump(directory_str) => {
+                    let v = process_path(directory_str.trim().trim_end_matches('/'))
+                        .to_string().add("/");
+                    result.push(v);
+                }
+                Node::File(virtual_file_path_str) => {
+                    let v = process_path(virtual_file_path_str);
+                    result.push(v);
+                }
+                _ => { continue; }
+            }
+        }
+    }
+
+    // Short names
+    if completion_mode {
+        for (k, _v) in folder_map.short_file_map {
+            result.push(format!(":{}", k));
+        }
+    }
+
+    result
 }
\ No newline at end of file
@@ -1,3 +1,4 @@
+use crate::cli_commands::cli_command_client::dry_run::{client_dry_run, DryRunOperation};
 use crate::cli_commands::cli_command_client::param_comp::comp::comp_param_from;
 use crate::cli_commands::cli_command_client::param_comp::data::{CompConfig, CompContext};
 use crate::cli_commands::client::{exec, RemoveArgs};
@@ -5,6 +6,11 @@ use crate::data::client_result::ClientResult;
 
 pub async fn client_remove(args: RemoveArgs) -> Option<ClientResult> {
 
+    // Preview only
+    if args.dry_run {
+        return client_dry_run(DryRunOperation::Remove { get: args.get }, &args.from_search, None).await;
+    }
+
     // Create result struct
     let mut result = ClientResult::result().await;
 
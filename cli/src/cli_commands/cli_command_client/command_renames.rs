@@ -0,0 +1,72 @@
+use colored::Colorize;
+use jam_ready::utils::local_archive::LocalArchive;
+use jam_ready::utils::text_process::process_path_text;
+use crate::cli_commands::client::{exec, RenamesArgs};
+use crate::data::client_result::ClientResult;
+use crate::data::database::Database;
+use crate::data::local_file_map::LocalFileMap;
+
+pub async fn client_renames(args: RenamesArgs) -> Option<ClientResult> {
+
+    // Create result struct
+    let mut result = ClientResult::result().await;
+
+    // Read local, remote database
+    let mut local = LocalFileMap::read().await;
+    let database = Database::read().await;
+
+    // Match untracked files with missing tracked files
+    let moved = local.detect_moved_files(&database);
+    if moved.is_empty() {
+        result.log("No moved or renamed files found.");
+        return Some(result);
+    }
+
+    for (_, from, to) in &moved {
+        result.log(format!("{} -> {}", from, to.cyan()).as_str());
+    }
+
+    if !args.apply {
+        result.log("Apply with: jam renames --apply");
+        return Some(result);
+    }
+
+    // Files whose server path differs from the new local path (Server path, New path)
+    let (from, to): (Vec<String>, Vec<String>) = moved.iter()
+        .filter_map(|(uuid, _, to)| {
+            let server_path = database.file_with_uuid(uuid.clone())?.path();
+            if server_path == process_path_text(to.clone()) { None } else { Some((server_path, to.clone())) }
+        })
+        .unzip();
+
+    if !from.is_empty() {
+
+        // Acquire file lock if requested
+        if args.get {
+            result.combine_unchecked(exec(vec!["file".to_string(), "get".to_string(), from.join("|")]).await);
+        }
+
+        // Move remote files
+        result.combine_unchecked(exec(vec!["file".to_string(), "move".to_string(), from.join("|"), to.join("|")]).await);
+    }
+
+    // Only relink files the server has moved (the database was synced by the move)
+    let database = Database::read().await;
+    let mut relinked = 0;
+    for (uuid, _, to) in moved {
+        let moved_on_server = database.file_with_uuid(uuid.clone())
+            .map(|file| file.path() == process_path_text(to.clone()))
+            .unwrap_or(false);
+
+        if moved_on_server {
+            local.relocate(&uuid, to);
+            relinked += 1;
+        } else {
+            result.warn(format!("Not moved on the server: {}", to).as_str());
+        }
+    }
+    LocalFileMap::update(&local).await;
+
+    result.log(format!("Relinked {} file(s).", relinked).as_str());
+    Some(result)
+}
@@ -1,13 +1,33 @@
+use jam_ready::utils::local_archive::LocalArchive;
+use crate::cli_commands::cli_command_client::dry_run::{client_dry_run, DryRun, DryRunOperation};
 use crate::cli_commands::cli_command_client::param_comp::comp::comp_param_from;
 use crate::cli_commands::cli_command_client::param_comp::data::{CompConfig, CompContext};
 use crate::cli_commands::client::{exec, RollbackArgs};
 use crate::data::client_result::ClientResult;
+use crate::data::database::Database;
 
 pub async fn client_rollback(args: RollbackArgs) -> Option<ClientResult> {
 
     // Create result struct
     let mut result = ClientResult::result().await;
 
+    // Rollback the whole changeset
+    if args.changeset {
+        if args.dry_run {
+            return client_rollback_changeset_dry_run(args).await;
+        }
+        return client_rollback_changeset(args, result).await;
+    }
+
+    // Preview only
+    if args.dry_run {
+        let Some(to_version) = args.to_version else {
+            result.err_and_end("Please specify the version to roll back to.");
+            return None;
+        };
+        return client_dry_run(DryRunOperation::Rollback { version: to_version, get: args.get }, &args.from_search, None).await;
+    }
+
     // Create compile config
     let config = CompConfig::read().await;
 
@@ -18,6 +38,11 @@ pub async fn client_rollback(args: RollbackArgs) -> Option<ClientResult> {
         return None;
     };
 
+    let Some(to_version) = args.to_version else {
+        result.err_and_end("Please specify the version to roll back to.");
+        return None;
+    };
+
     // Theoretically Rollback shouldn't support multi-directory operations, but I still want to add it :)))))
 
     if args.get {
@@ -25,11 +50,11 @@ pub async fn client_rollback(args: RollbackArgs) -> Option<ClientResult> {
         result.combine_unchecked(exec(vec!["file".to_string(), "get".to_string(), from.to_string()]).await);
     }
     // Rollback version
-    result.combine_unchecked(exec(vec!["file".to_string(), "rollback".to_string(), from.to_string(), (&args.to_version).to_string()]).await);
+    result.combine_unchecked(exec(vec!["file".to_string(), "rollback".to_string(), from.to_string(), to_version.to_string()]).await);
 
     // Directly re-download the file
     if args.back {
-        result.combine_unchecked(exec(vec!["view".to_string(), from.to_string(), args.to_version.to_string()]).await);
+        result.combine_unchecked(exec(vec!["view".to_string(), from.to_string(), to_version.to_string()]).await);
     }
 
     // No results
@@ -39,4 +64,74 @@ pub async fn client_rollback(args: RollbackArgs) -> Option<ClientResult> {
         result.log("No result");
         Some(result)
     }
-}
\ No newline at end of file
+}
+
+/// Rollback all files of a changeset to their previous versions
+async fn client_rollback_changeset(args: RollbackArgs, mut result: ClientResult) -> Option<ClientResult> {
+    let id = args.from_search.trim().to_string();
+
+    // Find the files of the changeset
+    let database = Database::read().await;
+    let Some(changeset) = database.changeset(&id) else {
+        result.err_and_end(format!("Changeset '{}' not found", id).as_str());
+        return None;
+    };
+    let mut files = Vec::new();
+    for (uuid, (before, _)) in &changeset.files {
+        if let Some(file) = database.file_with_uuid(uuid.clone()) {
+            files.push((file.path(), *before));
+        }
+    }
+    let paths = files.iter()
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<String>>()
+        .join("|");
+
+    if args.get {
+        // Acquire file locks
+        result.combine_unchecked(exec(vec!["file".to_string(), "get".to_string(), paths]).await);
+    }
+
+    // Rollback changeset
+    result.combine_unchecked(exec(vec!["file".to_string(), "rollback_changeset".to_string(), id]).await);
+
+    // Directly re-download the files
+    if args.back {
+        for (path, version) in files {
+            result.combine_unchecked(exec(vec!["view".to_string(), path, version.to_string()]).await);
+        }
+    }
+
+    Some(result)
+}
+
+/// Preview the rollback of a changeset
+async fn client_rollback_changeset_dry_run(args: RollbackArgs) -> Option<ClientResult> {
+    let id = args.from_search.trim().to_string();
+    let mut dry_run = DryRun::begin().await;
+
+    // Find the files of the changeset
+    let Some(changeset) = dry_run.config().database.changeset(&id) else {
+        return dry_run.err_and_end(format!("Changeset '{}' not found", id).as_str());
+    };
+    if changeset.reverted {
+        return dry_run.err_and_end(format!("Changeset '{}' has already been rolled back", id).as_str());
+    }
+    let mut files = Vec::new();
+    for (uuid, (before, after)) in &changeset.files {
+        if let Some(file) = dry_run.config().database.file_with_uuid(uuid.clone()) {
+            files.push((file.path(), *before, file.version() == *after));
+        }
+    }
+
+    // The whole changeset fails if any file has changed since
+    for (path, version, unchanged) in files {
+        if !unchanged {
+            dry_run.reject(format!("File '{}' has changed since this changeset", path).as_str());
+            continue;
+        }
+        dry_run.preview(&DryRunOperation::Rollback { version, get: args.get }, &path, None);
+    }
+
+    dry_run.end()
+}
@@ -0,0 +1,9 @@
+use crate::data::client_result::ClientResult;
+use crate::service::jam_peer::jam_peer_entry;
+
+pub async fn client_share() -> Option<ClientResult> {
+
+    // Serve cached versions to other clients until stopped
+    jam_peer_entry().await;
+    None
+}
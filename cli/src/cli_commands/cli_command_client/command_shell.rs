@@ -0,0 +1,200 @@
+use crate::cli_commands::client::{client_workspace_main, print_client_result, ClientWorkspaceEntry};
+use crate::cli_commands::completions::complete_search;
+use crate::data::client_result::ClientResult;
+use crate::data::database::Database;
+use crate::data::local_file_map::LocalFileMap;
+use crate::data::workspace::Workspace;
+use crate::service::jam_client::{begin_session, end_session, subscribe_server_events};
+use clap::{CommandFactory, Parser};
+use colored::Colorize;
+use jam_ready::utils::local_archive::{clear_archive_cache, enable_archive_cache, LocalArchive};
+use jam_ready::utils::text_process::split_to_args;
+use rustyline::completion::{Completer, Pair};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, ExternalPrinter, Helper};
+use std::env::current_dir;
+use tokio::runtime::Handle;
+use tokio::spawn;
+use tokio::task::block_in_place;
+
+/// Leave the shell
+const SHELL_EXIT: [&str; 2] = ["exit", "quit"];
+
+/// Read the workspace from disk again (after changes made outside the shell)
+const SHELL_RELOAD: &str = "reload";
+
+pub async fn client_shell() -> Option<ClientResult> {
+
+    // Create result struct
+    let result = ClientResult::result().await;
+
+    if Workspace::read().await.client.is_none() {
+        result.err_and_end("It's not a client workspace.");
+        return None;
+    }
+
+    let Ok(mut editor) = Editor::<ShellHelper, DefaultHistory>::new() else {
+        result.err_and_end("Failed to start the shell");
+        return None;
+    };
+    editor.set_helper(Some(ShellHelper::new()));
+    let history = current_dir().ok().map(|current| current.join(env!("FILE_SHELL_HISTORY")));
+    if let Some(history) = &history {
+        let _ = editor.load_history(history);
+    }
+
+    // Keep the workspace in memory and hold one server session
+    enable_archive_cache();
+    begin_session().await;
+
+    // Print changes made by teammates above the prompt
+    let notifications = editor.create_external_printer().ok().map(|mut printer| spawn(async move {
+        let Some(mut events) = subscribe_server_events().await else { return; };
+        while let Some(event) = events.recv().await {
+            let _ = printer.print(format!("{} {}", "*".bright_cyan(), event));
+        }
+    }));
+
+    println!("Type {} to leave, {} after changing the workspace outside the shell.", "exit".yellow(), SHELL_RELOAD.yellow());
+
+    loop {
+        let prompt = render_prompt().await;
+
+        // Completion reads the workspace through the runtime, so the editor may block this thread
+        let line = match block_in_place(|| editor.readline(&prompt)) {
+            Ok(line) => line,
+
+            // Ctrl + C clears the line
+            Err(ReadlineError::Interrupted) => continue,
+
+            // Ctrl + D or closed input
+            Err(_) => break,
+        };
+        let line = line.trim();
+        if line.is_empty() { continue; }
+        let _ = editor.add_history_entry(line);
+
+        if SHELL_EXIT.contains(&line) {
+            break;
+        }
+        if line == SHELL_RELOAD {
+            clear_archive_cache();
+            continue;
+        }
+
+        let mut args = vec!["jam".to_string()];
+        args.extend(split_to_args(line.trim_start_matches("jam ").to_string()));
+        if args.get(1).map(|command| command == "shell").unwrap_or(false) {
+            println!("{}", "Already in the shell.".bright_yellow());
+            continue;
+        }
+
+        // Report parse errors, the command line interface ignores them silently
+        if let Err(err) = ClientWorkspaceEntry::try_parse_from(args.clone()) {
+            let _ = err.print();
+            continue;
+        }
+        print_client_result(Box::pin(client_workspace_main(args)).await);
+    }
+
+    if let Some(history) = &history {
+        let _ = editor.save_history(history);
+    }
+    if let Some(notifications) = notifications {
+        notifications.abort();
+    }
+    end_session().await;
+    clear_archive_cache();
+
+    Some(result)
+}
+
+/// Workspace name, held locks and files waiting for commit
+async fn render_prompt() -> String {
+    let Some(client) = Workspace::read().await.client else {
+        return "jam > ".to_string();
+    };
+    let database = Database::read().await;
+    let local = LocalFileMap::read().await;
+
+    let held = database.files().iter()
+        .filter(|file| file.get_locker_owner_uuid()
+            .map(|owner| owner.trim() == client.uuid.trim())
+            .unwrap_or(false))
+        .count();
+    let completed = local.file_paths.values()
+        .filter(|local_file| local_file.completed)
+        .count();
+
+    format!("{} [{} {}] > ",
+        client.workspace_name.cyan(),
+        format!("{} held", held).yellow(),
+        format!("{} completed", completed).green())
+}
+
+/// Tab completion of commands, virtual paths, short names and parameters
+struct ShellHelper {
+    /// Command names and aliases
+    commands: Vec<String>,
+}
+
+impl ShellHelper {
+    fn new() -> Self {
+        let mut commands = vec![SHELL_RELOAD.to_string()];
+        commands.extend(SHELL_EXIT.iter().map(|command| command.to_string()));
+        for command in ClientWorkspaceEntry::command().get_subcommands() {
+            if command.is_hide_set() { continue; }
+            commands.push(command.get_name().to_string());
+            commands.extend(command.get_visible_aliases().map(|alias| alias.to_string()));
+        }
+        commands.sort();
+        commands.dedup();
+        Self { commands }
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let start = line.rfind([' ', '"', '\'']).map(|index| index + 1).unwrap_or(0);
+        let word = &line[start..];
+
+        // Command
+        if line[..start].trim().is_empty() {
+            let candidates = self.commands.iter()
+                .filter(|command| command.starts_with(word))
+                .map(|command| candidate(command.clone()))
+                .collect();
+            return Ok((start, candidates));
+        }
+
+        // Virtual paths, local files, short names (":name") and parameters ("name?")
+        let candidates = Handle::current().block_on(complete_search(word)).into_iter()
+            .map(candidate)
+            .collect();
+        Ok((start, candidates))
+    }
+}
+
+fn candidate(replacement: String) -> Pair {
+    Pair {
+        display: replacement.clone(),
+        replacement,
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
@@ -0,0 +1,50 @@
+use crate::cli_commands::client::SparseArgs;
+use crate::data::client_result::{ClientResult, ClientResultQueryProcess};
+use crate::data::workspace::Workspace;
+use jam_ready::utils::local_archive::LocalArchive;
+
+pub async fn client_sparse(args: SparseArgs) -> Option<ClientResult> {
+
+    let mut workspace = Workspace::read().await;
+    let Some(client) = &mut workspace.client else {
+        let result = ClientResult::result().await;
+        result.err_and_end("It's not a client workspace.");
+        return None;
+    };
+
+    let modify = args.clear || args.include.is_some() || args.exclude.is_some() || args.remove.is_some();
+
+    if args.clear {
+        client.sparse_include.clear();
+        client.sparse_exclude.clear();
+    }
+    if let Some(pattern) = args.remove {
+        client.sparse_include.retain(|include| include != &pattern);
+        client.sparse_exclude.retain(|exclude| exclude != &pattern);
+    }
+    if let Some(pattern) = args.include
+        && !client.sparse_include.contains(&pattern) {
+        client.sparse_include.push(pattern);
+    }
+    if let Some(pattern) = args.exclude
+        && !client.sparse_exclude.contains(&pattern) {
+        client.sparse_exclude.push(pattern);
+    }
+
+    // List patterns
+    let mut result = ClientResult::query(ClientResultQueryProcess::line_by_line).await;
+    if client.sparse_include.is_empty() && client.sparse_exclude.is_empty() {
+        result.log("Full checkout, no sparse patterns.");
+    }
+    for pattern in &client.sparse_include {
+        result.log(format!("+ {}", pattern).as_str());
+    }
+    for pattern in &client.sparse_exclude {
+        result.log(format!("- {}", pattern).as_str());
+    }
+
+    if modify {
+        Workspace::update(&workspace).await;
+    }
+    Some(result)
+}
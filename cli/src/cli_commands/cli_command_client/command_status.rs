@@ -0,0 +1,10 @@
+use crate::cli_commands::client::{exec, StatusArgs};
+use crate::data::client_result::ClientResult;
+
+pub async fn client_status(args: StatusArgs) -> Option<ClientResult> {
+    if args.json {
+        exec(vec!["status".to_string(), "json".to_string()]).await
+    } else {
+        exec(vec!["status".to_string()]).await
+    }
+}
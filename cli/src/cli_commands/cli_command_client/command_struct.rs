@@ -24,6 +24,7 @@ pub async fn client_struct(args: StructArgs) -> Option<ClientResult> {
     if flags.is_empty() {
         flags = "zhundecmg".to_string();
     }
+    if args.all { flags.push('a'); }
 
     exec(vec!["struct".to_string(), env_flags, flags]).await
 }
\ No newline at end of file
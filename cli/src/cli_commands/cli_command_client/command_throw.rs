@@ -1,3 +1,4 @@
+use crate::cli_commands::cli_command_client::dry_run::{client_dry_run, DryRunOperation};
 use crate::cli_commands::cli_command_client::param_comp::comp::comp_param_from;
 use crate::cli_commands::cli_command_client::param_comp::data::{CompConfig, CompContext};
 use crate::cli_commands::client::{exec, SearchArgs};
@@ -5,6 +6,11 @@ use crate::data::client_result::ClientResult;
 
 pub async fn client_throw(args: SearchArgs) -> Option<ClientResult> {
 
+    // Preview only
+    if args.dry_run {
+        return client_dry_run(DryRunOperation::Throw, &args.search, None).await;
+    }
+
     // Create result struct
     let mut result = ClientResult::result().await;
 
@@ -2,6 +2,8 @@ use crate::cli_commands::client::{exec, UpdateArgs};
 use crate::data::client_result::ClientResult;
 use crate::data::database::Database;
 use crate::data::local_file_map::LocalFileMap;
+use crate::data::workspace::Workspace;
+use jam_ready::utils::file_digest::md5_digest;
 use jam_ready::utils::file_operation::move_file;
 use jam_ready::utils::local_archive::LocalArchive;
 use jam_ready::utils::text_process::process_path_text;
@@ -22,13 +24,42 @@ pub async fn client_update(args: UpdateArgs) -> Option<ClientResult> {
 
         // Sync local file structure with remote
         result.log("Sync File Struct.");
-        sync_file_struct().await;
+        sync_file_struct(&mut result).await;
         result.log("Ok");
 
         // Remove all empty directories locally
         result.log("Remove Empty Directories.");
         if let Ok(current) = current_dir() {
-            let _ = remove_unused_directory(current);
+            for path in remove_unused_directory(current).unwrap_or_default() {
+                result.log(format!("Removed empty directory: {}", path.display()).as_str());
+            }
+        }
+        result.log("Ok");
+    }
+
+    if args.files {
+
+        // Download outdated files, locally modified files are left untouched
+        result.log("Download Outdated Files.");
+        let (outdated, conflicts) = collect_outdated_files().await;
+        if !outdated.is_empty() {
+            result.combine_unchecked(exec(vec!["view".to_string(), outdated.join("|")]).await);
+        }
+        for path in &conflicts {
+            result.warn(format!("Conflict: {} is modified locally, skipped", path).as_str());
+        }
+        result.log(format!("Ok, {} downloaded, {} skipped", outdated.len(), conflicts.len()).as_str());
+    }
+
+    if args.permissions {
+
+        // Files held by other members are read-only
+        result.log("Apply File Permissions.");
+        let database = Database::read().await;
+        let local = LocalFileMap::read().await;
+        let self_uuid = Workspace::read().await.client.map(|client| client.uuid).unwrap_or_default();
+        for path in local.apply_permissions(&database, &self_uuid) {
+            result.log(format!("Changed {}", path).as_str());
         }
         result.log("Ok");
     }
@@ -36,8 +67,44 @@ pub async fn client_update(args: UpdateArgs) -> Option<ClientResult> {
     Some(result)
 }
 
+/// Tracked files of the sparse checkout behind the server version (Outdated paths, Locally modified paths)
+async fn collect_outdated_files() -> (Vec<String>, Vec<String>) {
+    let database = Database::read().await;
+    let local = LocalFileMap::read().await;
+    let client = Workspace::read().await.client;
+    let mut outdated = Vec::new();
+    let mut conflicts = Vec::new();
+    let Ok(current) = current_dir() else { return (outdated, conflicts); };
+
+    for (uuid, local_file) in &local.file_paths {
+        let Some(file) = database.file_with_uuid(uuid.clone()) else { continue; };
+        if file.path().is_empty() || local_file.local_version >= file.version() {
+            continue;
+        }
+
+        // Files outside the sparse checkout are only pulled on demand
+        if client.as_ref().map(|client| !client.in_sparse(&file.path())).unwrap_or(false) {
+            continue;
+        }
+
+        // Missing files are downloaded again
+        let local_path = current.join(&local_file.local_path);
+        let modified = local_path.exists() &&
+            md5_digest(local_path).map(|digest| digest != local_file.local_digest).unwrap_or(true);
+        if modified {
+            conflicts.push(file.path());
+        } else {
+            outdated.push(file.path());
+        }
+    }
+
+    outdated.sort();
+    conflicts.sort();
+    (outdated, conflicts)
+}
+
 /// Sync local file structure with remote
-async fn sync_file_struct() {
+async fn sync_file_struct(result: &mut ClientResult) {
 
     // Local files and database
     let database = Database::read().await;
@@ -67,11 +134,11 @@ async fn sync_file_struct() {
                     // Process file move
                     match move_file(&from, &to) {
                         Ok(_) => {
-                            println!("Ok: Move {} to {}", from_str, to_str);
+                            result.log(format!("Move {} to {}", from_str, to_str).as_str());
                             success_uuid.push(uuid.clone());
                         }
                         Err(err) => {
-                            eprintln!("Err: Move {} to {} failed: {}", from_str, to_str, err);
+                            result.err(format!("Move {} to {} failed: {}", from_str, to_str, err).as_str());
                         }
                     }
                 }
@@ -114,8 +181,8 @@ async fn sync_file_struct() {
     LocalFileMap::update(&local).await;
 }
 
-/// Remove all empty directories
-pub fn remove_unused_directory(dir_path: PathBuf) -> io::Result<()> {
+/// Remove all empty directories (Removed directories)
+pub fn remove_unused_directory(dir_path: PathBuf) -> io::Result<Vec<PathBuf>> {
     if !dir_path.exists() {
         return Err(Error::new(
             ErrorKind::NotFound,
@@ -130,7 +197,7 @@ pub fn remove_unused_directory(dir_path: PathBuf) -> io::Result<()> {
         ));
     }
 
-    fn remove_empty_dirs(path: &Path) -> io::Result<bool> {
+    fn remove_empty_dirs(path: &Path, removed: &mut Vec<PathBuf>) -> io::Result<bool> {
         if !path.is_dir() {
             return Ok(false);
         }
@@ -146,7 +213,7 @@ pub fn remove_unused_directory(dir_path: PathBuf) -> io::Result<()> {
             }
 
             if entry_path.is_dir() {
-                let has_sub_entries = remove_empty_dirs(&entry_path)?;
+                let has_sub_entries = remove_empty_dirs(&entry_path, removed)?;
                 has_entries = has_entries || has_sub_entries;
             } else {
                 has_entries = true;
@@ -155,13 +222,14 @@ pub fn remove_unused_directory(dir_path: PathBuf) -> io::Result<()> {
 
         if !has_entries {
             fs::remove_dir(path)?;
-            println!("Ok: Removed empty directory: {}", path.display());
+            removed.push(path.to_path_buf());
             Ok(false)
         } else {
             Ok(true)
         }
     }
 
-    remove_empty_dirs(&dir_path)?;
-    Ok(())
+    let mut removed = Vec::new();
+    remove_empty_dirs(&dir_path, &mut removed)?;
+    Ok(removed)
 }
\ No newline at end of file
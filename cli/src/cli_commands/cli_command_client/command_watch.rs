@@ -0,0 +1,134 @@
+use crate::cli_commands::client::{exec, WatchArgs};
+use crate::data::client_result::ClientResult;
+use crate::data::database::Database;
+use crate::data::local_file_map::LocalFileMap;
+use crate::data::workspace::Workspace;
+use jam_ready::utils::file_digest::md5_digest;
+use jam_ready::utils::local_archive::LocalArchive;
+use jam_ready::utils::text_process::process_path_text;
+use notify::{recommended_watcher, Event, EventKind, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::env::current_dir;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::select;
+use tokio::signal::ctrl_c;
+use tokio::sync::mpsc::unbounded_channel;
+use tokio::time::sleep;
+
+/// Quiet time before changed files are checked (editors often write a file several times)
+const WATCH_DEBOUNCE_MILLIS: u64 = 800;
+
+pub async fn client_watch(args: WatchArgs) -> Option<ClientResult> {
+
+    // Create result struct
+    let mut result = ClientResult::result().await;
+
+    let Ok(current) = current_dir() else { return None; };
+    let Some(client) = Workspace::read().await.client else {
+        result.err_and_end("It's not a client workspace.");
+        return None;
+    };
+
+    // Forward file system events to the async loop
+    let (sender, mut receiver) = unbounded_channel::<PathBuf>();
+    let watcher = recommended_watcher(move |event: notify::Result<Event>| {
+        let Ok(event) = event else { return; };
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) { return; }
+        for path in event.paths {
+            let _ = sender.send(path);
+        }
+    });
+    let Ok(mut watcher) = watcher else {
+        result.err_and_end("Failed to start the file watcher");
+        return None;
+    };
+    if let Err(err) = watcher.watch(&current, RecursiveMode::Recursive) {
+        result.err_and_end(format!("Failed to watch the workspace: {}", err).as_str());
+        return None;
+    }
+
+    result.log(format!("Watching \"{}\", press Ctrl + C to stop.", current.display()).as_str());
+
+    let mut changed = HashSet::new();
+    loop {
+        select! {
+
+            // Ctrl + C shutdown
+            Ok(()) = ctrl_c() => break,
+
+            // Collect changed paths
+            Some(path) = receiver.recv() => {
+                if let Ok(relative) = path.strip_prefix(&current) {
+                    changed.insert(process_path_text(relative.display().to_string()));
+                }
+            }
+
+            // Check them once no more events arrive
+            _ = sleep(Duration::from_millis(WATCH_DEBOUNCE_MILLIS)), if !changed.is_empty() => {
+                let paths: Vec<String> = changed.drain().collect();
+                check_changes(&mut result, &current, client.uuid.trim(), &args, paths).await;
+            }
+        }
+    }
+
+    Some(result)
+}
+
+/// Compare changed tracked files with their local digest, then complete them or warn
+async fn check_changes(result: &mut ClientResult, current: &Path, self_uuid: &str, args: &WatchArgs, paths: Vec<String>) {
+    let local = LocalFileMap::read().await;
+    let tracked: Vec<String> = paths.into_iter()
+        .filter(|path| local.file_uuids.contains_key(path))
+        .collect();
+    if tracked.is_empty() {
+        return;
+    }
+
+    // Refresh lock status
+    if exec(vec!["update".to_string(), "quiet".to_string()]).await.is_none() {
+        result.warn("Failed to sync the database, using the cached lock status.");
+    }
+
+    let database = Database::read().await;
+    let mut local = LocalFileMap::read().await;
+    let mut completed = false;
+    for path in tracked {
+        let Some(uuid) = local.file_uuids.get(&path).cloned() else { continue; };
+        let Some(file) = database.file_with_uuid(uuid.clone()) else { continue; };
+        let Some(local_file) = local.file_paths.get_mut(&uuid) else { continue; };
+        let Ok(digest) = md5_digest(current.join(&path)) else { continue; };
+
+        // Unchanged, or already completed with this content
+        if digest == local_file.local_digest { continue; }
+        if local_file.completed && local_file.completed_digest == digest { continue; }
+
+        match file.get_locker_owner_uuid() {
+            Some(owner) if owner.trim() == self_uuid => {}
+            Some(_) => {
+                result.warn(format!("'{}' is locked by another member, your changes cannot be committed!", path).as_str());
+                continue;
+            }
+            None if file.is_mergeable() => {}
+            None => {
+                result.warn(format!("'{}' is not locked, run \"jam get {}\" before committing.", path, path).as_str());
+                continue;
+            }
+        }
+
+        if args.no_complete {
+            result.log(format!("Modified {}", path).as_str());
+            continue;
+        }
+
+        local_file.completed = true;
+        local_file.completed_commit = args.message.clone();
+        local_file.completed_digest = digest;
+        completed = true;
+        result.log(format!("Completed {}", path).as_str());
+    }
+
+    if completed {
+        LocalFileMap::update(&local).await;
+    }
+}
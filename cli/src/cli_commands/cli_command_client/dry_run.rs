@@ -0,0 +1,268 @@
+use crate::cli_commands::cli_command_client::param_comp::comp::{comp_param_from, comp_param_to};
+use crate::cli_commands::cli_command_client::param_comp::data::{CompConfig, CompContext};
+use crate::cli_commands::client::exec;
+use crate::data::client_result::ClientResult;
+use crate::data::database::VirtualFile;
+use crate::service::commands::member_duties::DUTIES_METADATA;
+use colored::Colorize;
+use jam_ready::utils::file_digest::md5_digest;
+use jam_ready::utils::text_process::process_path_text;
+use std::env::current_dir;
+
+/// File operations that can be previewed with "--dry-run"
+pub enum DryRunOperation {
+    /// Create virtual file (acquire lock afterward if "get")
+    Add { get: bool },
+
+    /// Remove virtual file (acquire lock first if "get")
+    Remove { get: bool },
+
+    /// Move virtual file to the TO path (acquire lock first if "get")
+    Move { get: bool },
+
+    /// Rollback to version (acquire lock first if "get")
+    Rollback { version: u32, get: bool },
+
+    /// Acquire lock
+    Get { longer: bool },
+
+    /// Release lock
+    Throw,
+
+    /// Download version (latest if None, acquire lock afterward if "get")
+    View { version: Option<u32>, get: bool },
+
+    /// Turn mergeable mode on or off (Leader only)
+    Mergeable { on: bool },
+}
+
+/// Lock status of a file, seen from the current member
+#[derive(PartialEq)]
+enum LockState {
+    Held,
+    Other,
+    Free,
+}
+
+/// Dry Run
+/// Checks operations against a freshly synced database and reports the outcome, without changing anything
+pub struct DryRun {
+    config: CompConfig,
+    result: ClientResult,
+    pass: usize,
+    fail: usize,
+}
+
+impl DryRun {
+    /// Sync the database and read the compile config
+    pub async fn begin() -> DryRun {
+        let mut result = ClientResult::result().await;
+
+        // Sync database
+        result.combine_unchecked(exec(vec!["update".to_string()]).await);
+
+        DryRun {
+            config: CompConfig::read().await,
+            result,
+            pass: 0,
+            fail: 0,
+        }
+    }
+
+    /// Compile config of the synced database
+    pub fn config(&self) -> &CompConfig {
+        &self.config
+    }
+
+    /// Preview the operation on a single file
+    pub fn preview(&mut self, operation: &DryRunOperation, path: &str, to: Option<&str>) {
+        match check(&self.config, operation, path, to) {
+            Ok(action) => {
+                self.pass += 1;
+                self.result.log(format!("{} {}", "[ DRY RUN ]".cyan(), action).as_str());
+            }
+            Err(reason) => self.reject(&reason),
+        }
+    }
+
+    /// Record an operation that would fail
+    pub fn reject(&mut self, reason: &str) {
+        self.fail += 1;
+        self.result.warn(format!("{} {}", "[ DRY RUN ]".cyan(), reason).as_str());
+    }
+
+    /// Print the summary
+    pub fn end(mut self) -> Option<ClientResult> {
+        self.result.log(format!("{} file(s) would succeed, {} would fail. Nothing was changed.", self.pass, self.fail).as_str());
+        Some(self.result)
+    }
+
+    /// Report an error and end
+    pub fn err_and_end(self, msg: &str) -> Option<ClientResult> {
+        self.result.err_and_end(msg);
+        None
+    }
+}
+
+/// Preview a FROM_SEARCH (and TO_SEARCH) operation
+pub async fn client_dry_run(operation: DryRunOperation, from_search: &str, to_search: Option<&str>) -> Option<ClientResult> {
+    let mut dry_run = DryRun::begin().await;
+
+    // Compile FROM input
+    let from = comp_param_from(dry_run.config(), CompContext::input(from_search));
+    let Ok(from) = from else {
+        return dry_run.err_and_end(format!("{}", from.err().unwrap()).as_str());
+    };
+
+    // Compile TO input
+    let to = match to_search {
+        Some(to_search) => {
+            let to = comp_param_to(dry_run.config(), from.clone().next(to_search));
+            let Ok(to) = to else {
+                return dry_run.err_and_end(format!("{}", to.err().unwrap()).as_str());
+            };
+            if to.final_paths.len() != from.final_paths.len() {
+                return dry_run.err_and_end("The number of \"from\" and \"to\" parameters does not match.");
+            }
+            to.final_paths
+        }
+        None => Vec::new(),
+    };
+
+    // Mergeable mode is limited to the "Leader" duty
+    if let DryRunOperation::Mergeable { .. } = operation {
+        let duties = exec(vec!["duties".to_string()]).await
+            .and_then(|result| result.get_metadata(DUTIES_METADATA).cloned());
+        match duties {
+            Some(duties) if !duties.split(',').any(|duty| duty == "Leader") => {
+                for path in &from.final_paths {
+                    dry_run.reject(format!("Cannot change '{}', \"Leader\" duty required", path).as_str());
+                }
+                return dry_run.end();
+            }
+            Some(_) => {}
+            None => dry_run.result.warn("Unable to query your duties, the \"Leader\" duty is checked by the server."),
+        }
+    }
+
+    for (index, path) in from.final_paths.iter().enumerate() {
+        dry_run.preview(&operation, path, to.get(index).map(|to| to.as_str()));
+    }
+
+    dry_run.end()
+}
+
+/// Check what the server would do with a file
+fn check(config: &CompConfig, operation: &DryRunOperation, path: &str, to: Option<&str>) -> Result<String, String> {
+    let database = &config.database;
+
+    // Adding needs a free path instead of an existing file
+    if let DryRunOperation::Add { get } = operation {
+        let path = process_path_text(path.to_string());
+        if database.contains_path(&path) {
+            return Err(format!("'{}' already exists", path));
+        }
+        return Ok(format!("Would create {}'{}'", if *get { "and lock " } else { "" }, path));
+    }
+
+    let Some(file) = database.search_file(path.to_string()) else {
+        return Err(format!("'{}' not found", path));
+    };
+    let path = file.path();
+    let lock = lock_state(file, &config.self_uuid);
+
+    match operation {
+        DryRunOperation::Add { .. } => unreachable!(),
+
+        DryRunOperation::Remove { get } => {
+            let prefix = require_lock(&path, &lock, *get)?;
+            Ok(format!("Would {}remove '{}'", prefix, path))
+        }
+
+        DryRunOperation::Move { get } => {
+            let prefix = require_lock(&path, &lock, *get)?;
+            let Some(to) = to else {
+                return Err("Missing destination path".to_string());
+            };
+            let to = process_path_text(to.to_string());
+            if database.contains_path(&to) {
+                return Err(format!("Cannot move '{}', '{}' already exists", path, to));
+            }
+            Ok(format!("Would {}move '{}' to '{}'", prefix, path, to))
+        }
+
+        DryRunOperation::Rollback { version, get } => {
+            let prefix = require_lock(&path, &lock, *get)?;
+            if file.real_path_version(*version).is_none() {
+                return Err(format!("'{}' has no version {}", path, version));
+            }
+            Ok(format!("Would {}roll back '{}' from version {} to {}", prefix, path, file.version(), version))
+        }
+
+        DryRunOperation::Get { longer } => {
+            match lock {
+                LockState::Other => Err(format!("'{}' is locked by another team member", path)),
+                LockState::Held => Ok(format!("'{}' is already held by you", path)),
+                LockState::Free if *longer => Ok(format!("Would lock '{}' (long-term)", path)),
+                LockState::Free => Ok(format!("Would lock '{}'", path)),
+            }
+        }
+
+        DryRunOperation::Throw => {
+            if lock != LockState::Held {
+                return Err(format!("'{}' is not held by you", path));
+            }
+            Ok(format!("Would unlock '{}'", path))
+        }
+
+        DryRunOperation::View { version, get } => {
+            let version = version.unwrap_or(file.version());
+            if file.real_path_version(version).is_none() {
+                return Err(format!("'{}' has no version {}", path, version));
+            }
+            let mut action = format!("Would download '{}' (version {})", path, version);
+            if is_modified_locally(config, file) {
+                action.push_str(", overwriting local changes");
+            }
+            match (*get, lock) {
+                (true, LockState::Other) => return Err(format!("{}, but '{}' is locked by another team member", action, path)),
+                (true, LockState::Free) => action.push_str(" and lock it"),
+                _ => {}
+            }
+            Ok(action)
+        }
+
+        DryRunOperation::Mergeable { on } => {
+            if *on == file.is_mergeable() {
+                return Ok(format!("'{}' is already {}", path, if *on { "mergeable" } else { "unmergeable" }));
+            }
+            Ok(format!("Would mark '{}' as {}", path, if *on { "mergeable" } else { "unmergeable" }))
+        }
+    }
+}
+
+/// Operations on the file content require the lock ("lock and " if it would be acquired first)
+fn require_lock(path: &str, lock: &LockState, get: bool) -> Result<&'static str, String> {
+    match lock {
+        LockState::Held => Ok(""),
+        LockState::Other => Err(format!("'{}' is locked by another team member", path)),
+        LockState::Free if get => Ok("lock and "),
+        LockState::Free => Err(format!("Acquire lock of '{}' before file operations", path)),
+    }
+}
+
+fn lock_state(file: &VirtualFile, self_uuid: &str) -> LockState {
+    match file.get_locker_owner_uuid() {
+        Some(owner) if owner.trim() == self_uuid => LockState::Held,
+        Some(_) => LockState::Other,
+        None => LockState::Free,
+    }
+}
+
+fn is_modified_locally(config: &CompConfig, file: &VirtualFile) -> bool {
+    let Some(uuid) = config.database.uuid_of_path(file.path()) else { return false; };
+    let Some(local_file) = config.local_file_map.file_paths.get(&uuid) else { return false; };
+    let Ok(current_dir) = current_dir() else { return false; };
+    let Ok(digest) = md5_digest(current_dir.join(&local_file.local_path)) else { return false; };
+    digest != local_file.local_digest
+}
@@ -3,16 +3,28 @@ pub mod command_redirect;
 pub mod command_update;
 pub mod command_complete;
 pub mod command_commit;
+pub mod command_watch;
 pub mod command_struct;
+pub mod command_status;
 pub mod command_archive;
+pub mod command_cache;
 pub mod command_add;
+pub mod command_import;
 pub mod command_remove;
 pub mod command_move;
+pub mod command_renames;
 pub mod command_rollback;
 pub mod command_get;
 pub mod command_throw;
 pub mod command_view;
+pub mod command_diff;
+pub mod command_mergeable;
 pub mod command_param;
+pub mod command_sparse;
+pub mod command_share;
 pub mod command_doc;
+pub mod command_history;
+pub mod command_shell;
 
 pub mod param_comp;
+pub mod dry_run;
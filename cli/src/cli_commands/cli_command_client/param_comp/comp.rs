@@ -1,14 +1,20 @@
 use crate::cli_commands::cli_command_client::param_comp::data::{CompConfig, CompContext, CompError};
 use crate::data::database::{Database, VirtualFile};
+use crate::data::ignore_rules::glob_to_regex;
 use crate::data::parameters::read_parameter;
+use jam_ready::utils::file_digest::md5_digest;
 use jam_ready::utils::text_process::process_path_text;
 use regex::Regex;
+use std::env::current_dir;
+use std::path::Path;
 
 /// Compile FROM parameter
 pub fn comp_param_from(config: &CompConfig, mut context: CompContext) -> Result<CompContext, CompError> {
     context = comp_alias_param_tag(context)?;
+    context = comp_attribute_filter_tag(context)?;
     context = comp_short_path_tag(&config, context)?;
     context = comp_multi_file_regex_tag(&config, context)?;
+    context = comp_apply_attribute_filters(config, context)?;
     // context = comp_normalize(context)?;
 
     Ok(comp_final(context))
@@ -24,7 +30,7 @@ pub fn comp_param_to(config: &CompConfig, mut context: CompContext) -> Result<Co
 
 /// When result is empty
 pub fn comp_final(context: CompContext) -> CompContext {
-    if context.final_paths.len() <= 0 {
+    if context.final_paths.is_empty() {
         CompContext {
             ctx: get_path(&context.input.clone()),
             final_paths: vec![context.input.clone()],
@@ -141,7 +147,32 @@ pub fn comp_short_path_tag(config: &CompConfig, mut context: CompContext) -> Res
     Ok(raw)
 }
 
+/// Compile attribute filter tag ("Art/ @ext:png @locked-by:me")
+pub fn comp_attribute_filter_tag(mut context: CompContext) -> Result<CompContext, CompError> {
+    let raw = context.clone();
+    if !context.input.contains('@') {
+        return Ok(raw);
+    }
+
+    // Only filter tokens are removed, other words belong to the path ("Art/My @Home.png")
+    let mut path = Vec::new();
+    for part in context.input.split(' ') {
+        if is_attribute_filter(part) {
+            context.filters.push(part.to_string());
+        } else {
+            path.push(part);
+        }
+    }
+
+    // Modify input
+    context.input = path.join(" ").trim().to_string();
+
+    Ok(context)
+}
+
 /// Compile multi-file regex tag
+/// Paths containing "**" or "{a,b}" are matched as globs against the whole workspace,
+/// otherwise the last segment is matched as a regex (or a glob, if it is not a valid regex) in its directory
 pub fn comp_multi_file_regex_tag(config: &CompConfig, mut context: CompContext) -> Result<CompContext, CompError> {
     let raw = context.clone();
     let split = context.input.split('/');
@@ -149,16 +180,31 @@ pub fn comp_multi_file_regex_tag(config: &CompConfig, mut context: CompContext)
         return Ok(raw);
     };
 
+    let recursive = is_recursive_glob(&context.input);
+
     // If contains *, treat as regex, otherwise skip
-    if !regex_str.contains("*") {
+    if !recursive && !regex_str.contains("*") {
         return Ok(raw);
     }
-    let Ok(regex) = Regex::new(regex_str) else {
+
+    // Build matcher of the full path (recursive glob) or of the file name
+    let regex = if recursive {
+        Regex::new(&format!("^{}$", glob_to_regex(&context.input)))
+            .map_err(|_| CompError::err(format!("Failed to parse the glob pattern \"{}\".", context.input).as_str()))?
+    } else if let Ok(regex) = Regex::new(regex_str) {
+        regex
+    } else if let Ok(regex) = Regex::new(&format!("^{}$", glob_to_regex(regex_str))) {
+        regex
+    } else {
         return Err(CompError::err(format!("Failed to parse the regular expression \"{}\".", regex_str).as_str()));
     };
 
     // Set context
-    context.ctx = get_path(&context.input.clone());
+    context.ctx = if recursive {
+        get_glob_base(&context.input)
+    } else {
+        get_path(&context.input.clone())
+    };
 
     // If multi-path not allowed, end compilation
     if !config.allow_multi_path {
@@ -171,6 +217,12 @@ pub fn comp_multi_file_regex_tag(config: &CompConfig, mut context: CompContext)
     // Search for matching files in context directory
     for virtual_file in get_files_in_dir(&context.ctx, &config.database) {
         let path = virtual_file.path().clone();
+        if recursive {
+            if regex.is_match(&path) {
+                context.final_paths.push(path);
+            }
+            continue;
+        }
         let name = path.split("/").last();
         if let Some(name) = name {
             if regex.is_match(name) {
@@ -183,6 +235,147 @@ pub fn comp_multi_file_regex_tag(config: &CompConfig, mut context: CompContext)
     Ok(context)
 }
 
+/// Apply attribute filters to the compiled paths
+/// Without compiled paths, a directory (or empty input) selects all files inside it
+pub fn comp_apply_attribute_filters(config: &CompConfig, mut context: CompContext) -> Result<CompContext, CompError> {
+    if context.filters.is_empty() {
+        return Ok(context);
+    }
+
+    // Collect candidates
+    let candidates: Vec<String> = if !context.final_paths.is_empty() {
+        context.final_paths.clone()
+    } else if context.input.is_empty() || context.input.ends_with('/') {
+        context.ctx = context.input.clone();
+        get_files_in_dir(&context.input, &config.database).iter().map(|file| file.path()).collect()
+    } else {
+        config.database.search_file(context.input.clone()).map(|file| vec![file.path()]).unwrap_or_default()
+    };
+
+    // Keep the files matching every filter
+    let mut output = Vec::new();
+    for path in candidates {
+        let Some(file) = config.database.file(path.clone()) else { continue; };
+        let mut matched = true;
+        for filter in &context.filters {
+            if !match_attribute_filter(config, file, filter)? {
+                matched = false;
+                break;
+            }
+        }
+        if matched {
+            output.push(path);
+        }
+    }
+
+    if output.is_empty() {
+        return Err(CompError::err("No files match the filters."));
+    }
+    if output.len() > 1 && !config.allow_multi_path {
+        return Err(CompError::err("Multiple paths not allowed."));
+    }
+    output.sort();
+    context.final_paths = output;
+
+    Ok(context)
+}
+
+/// Check a file against an attribute filter
+fn match_attribute_filter(config: &CompConfig, file: &VirtualFile, filter: &str) -> Result<bool, CompError> {
+    let filter = filter.trim_start_matches('@');
+    let owner = file.get_locker_owner_uuid().map(|owner| owner.trim().to_string());
+
+    // Locks
+    if filter == "locked" {
+        return Ok(owner.is_some());
+    }
+    if filter == "unlocked" {
+        return Ok(owner.is_none());
+    }
+    if let Some(member) = filter.strip_prefix("locked-by:") {
+        let Some(owner) = owner else { return Ok(false); };
+        return Ok(match member {
+            "me" => owner == config.self_uuid,
+            "other" | "others" => owner != config.self_uuid,
+            uuid => owner == uuid.trim(),
+        });
+    }
+
+    // Extension ("ext:png" or "ext:png,jpg")
+    if let Some(extensions) = filter.strip_prefix("ext:") {
+        let path = file.path();
+        let Some(extension) = Path::new(&path).extension() else { return Ok(false); };
+        let extension = extension.to_string_lossy().to_lowercase();
+        return Ok(extensions.split(',').any(|ext| ext.trim().trim_start_matches('.').to_lowercase() == extension));
+    }
+
+    // Version ("version>3", "version<=2", "version=1")
+    if let Some(comparison) = filter.strip_prefix("version") {
+        let (operator, value) = ["!=", ">=", "<=", ">", "<", "=", ":"].iter()
+            .find_map(|operator| comparison.strip_prefix(operator).map(|value| (*operator, value)))
+            .ok_or_else(|| CompError::err(format!("Incorrect version filter \"@{}\".", filter).as_str()))?;
+        let Ok(value) = value.trim().parse::<u32>() else {
+            return Err(CompError::err(format!("Incorrect version filter \"@{}\".", filter).as_str()));
+        };
+        let version = file.version();
+        return Ok(match operator {
+            "!=" => version != value,
+            ">=" => version >= value,
+            "<=" => version <= value,
+            ">" => version > value,
+            "<" => version < value,
+            _ => version == value,
+        });
+    }
+
+    // Local state
+    if filter == "mergeable" {
+        return Ok(file.is_mergeable());
+    }
+    if filter == "modified" || filter == "outdated" {
+        let Some(uuid) = config.database.uuid_of_path(file.path()) else { return Ok(false); };
+        let Some(local_file) = config.local_file_map.file_paths.get(&uuid) else { return Ok(false); };
+        if filter == "outdated" {
+            return Ok(local_file.local_version < file.version());
+        }
+        let Ok(current_dir) = current_dir() else { return Ok(false); };
+        let Ok(digest) = md5_digest(current_dir.join(&local_file.local_path)) else { return Ok(false); };
+        return Ok(digest != local_file.local_digest);
+    }
+
+    Err(CompError::err(format!("Unknown filter \"@{}\".", filter).as_str()))
+}
+
+/// Check if a word is an attribute filter ("@locked", "@ext:png", "@version>3")
+fn is_attribute_filter(word: &str) -> bool {
+    let Some(filter) = word.strip_prefix('@') else { return false; };
+    match filter {
+        "locked" | "unlocked" | "mergeable" | "modified" | "outdated" => true,
+        _ => filter.starts_with("locked-by:")
+            || filter.starts_with("ext:")
+            || filter.strip_prefix("version").is_some_and(|comparison| comparison.starts_with(['!', '>', '<', '=', ':'])),
+    }
+}
+
+/// Check if the input is a recursive glob ("**" or "{a,b}")
+fn is_recursive_glob(input: &str) -> bool {
+    if input.contains("**") {
+        return true;
+    }
+    match (input.find('{'), input.rfind('}')) {
+        (Some(start), Some(end)) => start < end && input[start..end].contains(','),
+        _ => false,
+    }
+}
+
+/// Directory before the first glob character ("Art/**/*.png" -> "Art/")
+fn get_glob_base(glob: &str) -> String {
+    match glob.find(['*', '?', '[', '{']) {
+        Some(index) => get_path(&glob[..index]),
+        None => get_path(glob),
+    }
+}
+
 fn get_path(path: &str) -> String {
     if let Some(last_slash) = path.rfind('/') {
         path[..=last_slash].to_string()
@@ -233,4 +426,27 @@ pub fn get_files_in_dir<'a>(dir: &'a str, database: &'a Database) -> Vec<&'a Vir
         }
     }
     result
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split_filters(input: &str) -> (String, Vec<String>) {
+        let context = CompContext { input: input.to_string(), ..Default::default() };
+        let Ok(context) = comp_attribute_filter_tag(context) else { panic!("failed to compile \"{}\"", input) };
+        (context.input, context.filters)
+    }
+
+    #[test]
+    fn splits_filters_from_path() {
+        assert_eq!(split_filters("Art/ @ext:png @locked"), ("Art/".to_string(), vec!["@ext:png".to_string(), "@locked".to_string()]));
+        assert_eq!(split_filters("@version>=3 Art/"), ("Art/".to_string(), vec!["@version>=3".to_string()]));
+    }
+
+    #[test]
+    fn keeps_spaces_in_path() {
+        assert_eq!(split_filters("Art/My File.png @modified"), ("Art/My File.png".to_string(), vec!["@modified".to_string()]));
+        assert_eq!(split_filters("Art/My @Home.png"), ("Art/My @Home.png".to_string(), Vec::<String>::new()));
+    }
+}
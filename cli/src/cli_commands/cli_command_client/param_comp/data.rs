@@ -3,7 +3,9 @@ use std::fmt::{Debug, Display, Formatter};
 use jam_ready::utils::local_archive::LocalArchive;
 use crate::cli_commands::cli_command_client::param_comp::SPLIT_CHAR;
 use crate::data::database::Database;
+use crate::data::local_file_map::LocalFileMap;
 use crate::data::local_folder_map::LocalFolderMap;
+use crate::data::workspace::Workspace;
 
 pub struct CompConfig {
     /// Whether the expression allows multiple paths
@@ -13,7 +15,13 @@ pub struct CompConfig {
     pub local_folder_map: LocalFolderMap,
 
     /// Database used by the expression
-    pub database: Database
+    pub database: Database,
+
+    /// Local files used by attribute filters
+    pub local_file_map: LocalFileMap,
+
+    /// Current member used by attribute filters
+    pub self_uuid: String,
 }
 
 impl CompConfig {
@@ -22,6 +30,10 @@ impl CompConfig {
             allow_multi_path: true,
             local_folder_map: LocalFolderMap::read().await,
             database: Database::read().await,
+            local_file_map: LocalFileMap::read().await,
+            self_uuid: Workspace::read().await.client
+                .map(|client| client.uuid.trim().to_string())
+                .unwrap_or_default(),
         }
     }
 }
@@ -36,6 +48,9 @@ pub struct CompContext {
 
     /// Output paths
     pub final_paths: Vec<String>,
+
+    /// Attribute filters ("@ext:png")
+    pub filters: Vec<String>,
 }
 
 impl Display for CompContext {
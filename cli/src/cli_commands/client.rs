@@ -1,22 +1,33 @@
 use crate::cli_commands::cli_command_client::{
     command_add::client_add,
     command_archive::client_archive,
+    command_cache::client_cache,
     command_complete::client_complete,
     command_commit::client_commit,
+    command_diff::client_diff,
     command_get::client_get,
+    command_history::client_history,
+    command_import::client_import,
+    command_mergeable::client_mergeable,
     command_move::client_move,
+    command_renames::client_renames,
     command_param::client_param,
+    command_sparse::client_sparse,
+    command_share::client_share,
     command_query::client_query,
     command_redirect::client_redirect,
     command_remove::client_remove,
     command_rollback::client_rollback,
+    command_status::client_status,
     command_struct::client_struct,
     command_throw::client_throw,
     command_update::client_update,
     command_view::client_view,
+    command_watch::client_watch,
     command_doc::client_doc,
+    command_shell::client_shell,
 };
-use crate::data::client_result::ClientResult;
+use crate::data::client_result::{output_json, print_json_output, set_output_format, ClientResult, ClientResultQueryProcess, OutputFormat};
 use crate::help::help_docs::get_help_docs;
 use crate::service::jam_client::execute;
 use clap::{Args, Parser, Subcommand};
@@ -34,6 +45,10 @@ use std::ffi::OsString;
 pub struct ClientWorkspaceEntry {
     #[command(subcommand)]
     command: ClientCommands,
+
+    // Output format of the result
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
 }
 
 /// Client commands
@@ -59,6 +74,10 @@ enum ClientCommands {
         visible_alias = "ls")]
     Struct(StructArgs),
 
+    // Show what needs to be done in the working copy
+    #[command(visible_alias = "st")]
+    Status(StatusArgs),
+
     // ---------------------------
     // Workspace related
 
@@ -88,7 +107,18 @@ enum ClientCommands {
         visible_alias = "save",
         visible_alias = "sv"
     )]
-    Commit,
+    Commit(CommitArgs),
+
+    // Watch local files, complete edited files and warn about locks
+    #[command(visible_alias = "w")]
+    Watch(WatchArgs),
+
+    // Show changesets or file history
+    #[command(
+        visible_alias = "hist",
+        visible_alias = "log"
+    )]
+    History(HistoryArgs),
 
     // Archive database version (Leader only)
     Archive,
@@ -100,6 +130,10 @@ enum ClientCommands {
     )]
     Add(NewArgs),
 
+    // Add and commit all files of a local directory
+    #[command(visible_alias = "imp")]
+    Import(ImportArgs),
+
     // Remove file
     #[command(
         visible_alias = "rm",
@@ -115,6 +149,10 @@ enum ClientCommands {
     )]
     Move(MoveArgs),
 
+    // Detect locally moved or renamed files
+    #[command(visible_alias = "detect-moves")]
+    Renames(RenamesArgs),
+
     // Rollback file version
     #[command(
         visible_alias = "rb",
@@ -145,6 +183,19 @@ enum ClientCommands {
     )]
     View(ViewArgs),
 
+    // Compare local file with server versions
+    #[command(
+        visible_alias = "df",
+        visible_alias = "compare"
+    )]
+    Diff(DiffArgs),
+
+    // Allow merged commits without lock (Leader only)
+    #[command(
+        visible_alias = "merge"
+    )]
+    Mergeable(MergeableArgs),
+
     // Query documentation
     Doc(DocArgs),
 
@@ -155,7 +206,32 @@ enum ClientCommands {
     #[command(
         visible_alias = "set"
     )]
-    Param(ParamArgs)
+    Param(ParamArgs),
+
+    // Manage sparse checkout patterns
+    #[command(
+        visible_alias = "sp"
+    )]
+    Sparse(SparseArgs),
+
+    // Manage the local cache of downloaded versions
+    #[command(
+        visible_alias = "cc"
+    )]
+    Cache(CacheArgs),
+
+    // Share the local cache with other clients on the network
+    #[command(
+        visible_alias = "peer"
+    )]
+    Share,
+
+    // Run commands in an interactive shell, keeping the workspace in memory
+    #[command(
+        visible_alias = "sh",
+        visible_alias = "repl"
+    )]
+    Shell
 }
 
 // Client query commands
@@ -301,7 +377,26 @@ pub struct NewArgs {
 
     // Attempt to acquire lock
     #[arg(long, short = 'g', alias = "lock", alias = "l")]
-    pub get: bool
+    pub get: bool,
+
+    // Preview the operation without changing anything
+    #[arg(long = "dry-run", alias = "preview")]
+    pub dry_run: bool
+}
+
+/// Import parameters
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    // Local directory
+    pub directory: String,
+
+    // Changeset message
+    #[arg(long, short = 'm')]
+    pub message: Option<String>,
+
+    // Preview the operation without changing anything
+    #[arg(long = "dry-run", alias = "preview")]
+    pub dry_run: bool
 }
 
 /// Remove parameters
@@ -312,14 +407,22 @@ pub struct RemoveArgs {
 
     // Attempt to acquire lock
     #[arg(long, short = 'g', alias = "lock", alias = "l")]
-    pub get: bool
+    pub get: bool,
+
+    // Preview the operation without changing anything
+    #[arg(long = "dry-run", alias = "preview")]
+    pub dry_run: bool
 }
 
 /// Search (Path or Uuid) parameters
 #[derive(Args, Debug)]
 pub struct SearchArgs {
     // Search term
-    pub search: String
+    pub search: String,
+
+    // Preview the operation without changing anything
+    #[arg(long = "dry-run", alias = "preview")]
+    pub dry_run: bool
 }
 
 #[derive(Args, Debug)]
@@ -333,7 +436,43 @@ pub struct ViewArgs {
 
     // Attempt to acquire lock
     #[arg(long, short = 'g', alias = "lock", alias = "l")]
-    pub get: bool
+    pub get: bool,
+
+    // Preview the operation without changing anything
+    #[arg(long = "dry-run", alias = "preview")]
+    pub dry_run: bool
+}
+
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    // Search term
+    pub from_search: String,
+
+    // Version to compare with (default: latest)
+    #[arg(short, long)]
+    pub version: Option<u32>,
+
+    // Compare with the latest version instead of the local file
+    #[arg(long, short = 'r')]
+    pub remote: bool,
+
+    // Output as Json
+    #[arg(long, short = 'j')]
+    pub json: bool
+}
+
+#[derive(Args, Debug)]
+pub struct MergeableArgs {
+    // Search term
+    pub from_search: String,
+
+    // Turn off mergeable mode
+    #[arg(long)]
+    pub off: bool,
+
+    // Preview the operation without changing anything
+    #[arg(long = "dry-run", alias = "preview")]
+    pub dry_run: bool
 }
 
 #[derive(Args, Debug)]
@@ -349,7 +488,11 @@ pub struct GetArgs {
 
     // Long-term lock
     #[arg(short = 'l', long = "longer")]
-    pub longer: bool
+    pub longer: bool,
+
+    // Preview the operation without changing anything
+    #[arg(long = "dry-run", alias = "preview")]
+    pub dry_run: bool
 }
 
 /// Search (Path or Uuid) parameters
@@ -367,17 +510,36 @@ pub struct MoveArgs {
 
     // Only move local file
     #[arg(long, short = 'l')]
-    pub local: bool
+    pub local: bool,
+
+    // Preview the operation without changing anything
+    #[arg(long = "dry-run", alias = "preview")]
+    pub dry_run: bool
+}
+
+#[derive(Args, Debug)]
+pub struct RenamesArgs {
+    // Move the virtual files and relink the local files
+    #[arg(long, short = 'a')]
+    pub apply: bool,
+
+    // Attempt to acquire lock
+    #[arg(long, short = 'g', alias = "lock")]
+    pub get: bool
 }
 
 /// Rollback parameters
 #[derive(Args, Debug)]
 pub struct RollbackArgs {
-    // Search term
+    // Search term (or changeset Id with --changeset)
     pub from_search: String,
 
     // Version to rollback to
-    pub to_version: u32,
+    pub to_version: Option<u32>,
+
+    // Rollback a whole changeset
+    #[arg(long, short = 'c')]
+    pub changeset: bool,
 
     // Attempt to acquire lock
     #[arg(long, short = 'g', alias = "lock")]
@@ -386,6 +548,31 @@ pub struct RollbackArgs {
     // Download file after rollback
     #[arg(long, short = 'b')]
     pub back: bool,
+
+    // Preview the operation without changing anything
+    #[arg(long = "dry-run", alias = "preview")]
+    pub dry_run: bool
+}
+
+#[derive(Args, Debug)]
+pub struct CommitArgs {
+    // Changeset message
+    #[arg(long, short = 'm')]
+    pub message: Option<String>,
+
+    // Preview the operation without changing anything
+    #[arg(long = "dry-run", alias = "preview")]
+    pub dry_run: bool
+}
+
+#[derive(Args, Debug)]
+pub struct HistoryArgs {
+    // Search term (show history of files instead of changesets)
+    pub from_search: Option<String>,
+
+    // Show a single changeset
+    #[arg(long, short = 'c')]
+    pub changeset: Option<String>
 }
 
 #[derive(Args, Debug)]
@@ -394,7 +581,76 @@ pub struct CompleteArgs {
     pub from_search: String,
 
     // Commit message
-    pub info: Option<String>
+    pub info: Option<String>,
+
+    // Preview the operation without changing anything
+    #[arg(long = "dry-run", alias = "preview")]
+    pub dry_run: bool
+}
+
+#[derive(Args, Debug)]
+pub struct WatchArgs {
+    // Commit message of completed files
+    #[arg(long, short = 'm', default_value = "Update")]
+    pub message: String,
+
+    // Only warn, never mark files as completed
+    #[arg(long, short = 'n')]
+    pub no_complete: bool
+}
+
+#[derive(Args, Debug)]
+pub struct SparseArgs {
+    // Add an include pattern
+    #[arg(long, short = 'i')]
+    pub include: Option<String>,
+
+    // Add an exclude pattern
+    #[arg(long, short = 'e')]
+    pub exclude: Option<String>,
+
+    // Remove a pattern
+    #[arg(long, short = 'r')]
+    pub remove: Option<String>,
+
+    // Remove all patterns (full checkout)
+    #[arg(long)]
+    pub clear: bool
+}
+
+#[derive(Args, Debug)]
+pub struct CacheArgs {
+    // Set the size limit in MB (0 for unlimited)
+    #[arg(long, short = 'l')]
+    pub limit: Option<u64>,
+
+    // Remove versions older than the latest
+    #[arg(long, short = 'c')]
+    pub clean: bool,
+
+    // Remove all versions that are not pinned
+    #[arg(long)]
+    pub clear: bool,
+
+    // Pin a cached version, so it is never evicted
+    #[arg(long, short = 'p')]
+    pub pin: Option<String>,
+
+    // Unpin a cached version
+    #[arg(long, short = 'u')]
+    pub unpin: Option<String>,
+
+    // Version to pin or unpin (0 for the latest)
+    #[arg(long, short = 'v', default_value_t = 0)]
+    pub version: u32,
+
+    // Check cached files against the server digests
+    #[arg(long)]
+    pub verify: bool,
+
+    // List cached files
+    #[arg(long, short = 'a')]
+    pub list: bool
 }
 
 #[derive(Args, Debug)]
@@ -457,6 +713,17 @@ pub struct StructArgs {
     // Show moved files (based on remote/local switches)
     #[arg(long = "moved", short = 'm')]
     pub moved: bool,
+
+    // Include files outside the sparse checkout
+    #[arg(long = "all", short = 'a')]
+    pub all: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct StatusArgs {
+    // Output as Json
+    #[arg(long, short = 'j')]
+    pub json: bool
 }
 
 #[derive(Args, Debug)]
@@ -482,6 +749,14 @@ pub struct UpdateArgs {
 
     #[arg(short = 'd', long = "database", alias = "db", default_value = "true")]
     pub database : bool,
+
+    // Download newer versions of tracked files
+    #[arg(short = 'f', long = "files")]
+    pub files : bool,
+
+    // Make local files read-only unless held
+    #[arg(short = 'p', long = "permissions")]
+    pub permissions : bool,
 }
 
 pub async fn client_workspace_main<I, T>(itr: I) -> Option<ClientResult>
@@ -489,16 +764,28 @@ where
     I: IntoIterator<Item = T>,
     T: Into<OsString> + Clone {
     if args().count() <= 1 {
-        client_print_helps();
-        return None;
+        return client_print_helps().await;
     }
 
-    let Ok(cmd) = ClientWorkspaceEntry::try_parse_from(itr) else {
-        return None;
+    let itr: Vec<T> = itr.into_iter().collect();
+    let cmd = match ClientWorkspaceEntry::try_parse_from(itr.clone()) {
+        Ok(cmd) => cmd,
+
+        // Parse errors are only reported as JSON
+        Err(err) => {
+            if !requests_json_output(itr) {
+                return None;
+            }
+            set_output_format(OutputFormat::Json);
+            let mut result = ClientResult::result().await;
+            result.err(err.render().to_string().as_str());
+            return Some(result);
+        }
     };
+    set_output_format(cmd.output);
 
     match cmd.command {
-        ClientCommands::Help => client_print_helps(),
+        ClientCommands::Help => client_print_helps().await,
 
         ClientCommands::Query(command) => client_query(command).await,
 
@@ -509,18 +796,28 @@ where
 
         ClientCommands::Complete(args) => client_complete(args).await,
 
-        ClientCommands::Commit => client_commit().await,
+        ClientCommands::Commit(args) => client_commit(args).await,
+
+        ClientCommands::Watch(args) => client_watch(args).await,
+
+        ClientCommands::History(args) => client_history(args).await,
 
         ClientCommands::Struct(args) => client_struct(args).await,
 
+        ClientCommands::Status(args) => client_status(args).await,
+
         ClientCommands::Archive => client_archive().await,
 
         ClientCommands::Add(args) => client_add(args).await,
 
+        ClientCommands::Import(args) => client_import(args).await,
+
         ClientCommands::Remove(args) => client_remove(args).await,
 
         ClientCommands::Move(args) => client_move(args).await,
 
+        ClientCommands::Renames(args) => client_renames(args).await,
+
         ClientCommands::Rollback(args) => client_rollback(args).await,
 
         ClientCommands::Get(args) => client_get(args).await,
@@ -529,16 +826,38 @@ where
 
         ClientCommands::View(args) => client_view(args).await,
 
+        ClientCommands::Diff(args) => client_diff(args).await,
+
+        ClientCommands::Mergeable(args) => client_mergeable(args).await,
+
         ClientCommands::Param(args) => client_param(args).await,
 
+        ClientCommands::Sparse(args) => client_sparse(args).await,
+
+        ClientCommands::Cache(args) => client_cache(args).await,
+
+        ClientCommands::Share => client_share().await,
+
+        ClientCommands::Shell => client_shell().await,
+
         ClientCommands::Doc(args) => client_doc(args).await,
     }
 }
 
 /// Print client help
-fn client_print_helps() -> Option<ClientResult> {
-    println!("{}", get_help_docs("client_help"));
-    None
+async fn client_print_helps() -> Option<ClientResult> {
+    let mut query = ClientResult::query(ClientResultQueryProcess::direct).await;
+    query.log(get_help_docs("client_help").as_str());
+    Some(query)
+}
+
+/// Check if the command line asks for "--output json", before it is parsed
+fn requests_json_output<T: Into<OsString>>(itr: Vec<T>) -> bool {
+    let args: Vec<String> = itr.into_iter()
+        .map(|arg| arg.into().to_string_lossy().to_string())
+        .collect();
+    args.iter().any(|arg| arg == "--output=json") ||
+        args.windows(2).any(|pair| pair[0] == "--output" && pair[1] == "json")
 }
 
 /// Execute client command
@@ -551,4 +870,9 @@ pub fn print_client_result(result : Option<ClientResult>) {
     if let Some(result) = result {
         result.end_print();
     }
+
+    // Exactly one document per command, even when it ended without a result
+    if output_json() {
+        print_json_output();
+    }
 }
\ No newline at end of file
@@ -0,0 +1,319 @@
+use crate::cli_commands::cli_command_client::command_query::list_directory;
+use crate::cli_commands::client::ClientWorkspaceEntry;
+use crate::cli_commands::server::ServerWorkspaceEntry;
+use crate::cli_commands::setup::WorkspaceSetup;
+use crate::data::member::MemberDuty;
+use crate::data::parameters::parameters;
+use crate::data::workspace::Workspace;
+use crate::data::workspace::WorkspaceType::{Client, Server, Unknown};
+use clap::{Arg, Args, Command, CommandFactory, Parser, Subcommand, ValueEnum};
+use std::env::args;
+use strum::IntoEnumIterator;
+
+/// Command printing the completion script
+const COMMAND_COMPLETIONS: &str = "completions";
+
+/// Hidden command called back by the completion scripts
+const COMMAND_CALLBACK: &str = "__complete";
+
+/// Completion commands (available in every workspace)
+#[derive(Parser, Debug)]
+#[command(
+    disable_help_flag = true,
+    disable_version_flag = true,
+    disable_help_subcommand = true,
+    help_template = "{all-args}"
+)]
+struct CompletionsEntry {
+    #[command(subcommand)]
+    command: CompletionsCommands,
+}
+
+#[derive(Subcommand, Debug)]
+enum CompletionsCommands {
+
+    // Print the completion script of a shell
+    Completions(CompletionsArgs),
+
+    // Print the candidates of the word being completed
+    #[command(name = "__complete", hide = true)]
+    Callback(CallbackArgs),
+}
+
+#[derive(Args, Debug)]
+struct CompletionsArgs {
+
+    // Target shell
+    shell: CompletionShell,
+}
+
+#[derive(Args, Debug)]
+struct CallbackArgs {
+
+    // Index of the word being completed (the first word after "jam" is 1)
+    position: usize,
+
+    // Words of the command line, without "jam"
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    words: Vec<String>,
+}
+
+/// Supported shells
+#[derive(ValueEnum, Clone, Debug)]
+enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+    #[value(alias = "pwsh")]
+    Powershell,
+}
+
+/// Bash (":" breaks words in bash, so the line is split here and the prefix trimmed afterward)
+const SCRIPT_BASH: &str = r##"# jam completions (bash)
+# Load with: source <(jam completions bash)
+_jam_completions() {
+    local line="${COMP_LINE:0:COMP_POINT}"
+    local -a words
+    read -ra words <<< "$line"
+    [[ "$line" == *[[:space:]] ]] && words+=("")
+    local position=$(( ${#words[@]} - 1 ))
+    local cur="${words[position]}"
+    local IFS=$'\n'
+    COMPREPLY=($(jam __complete "$position" "${words[@]:1}" 2>/dev/null))
+    if [[ "$cur" == *:* && "$COMP_WORDBREAKS" == *:* ]]; then
+        local prefix="${cur%"${cur##*:}"}"
+        COMPREPLY=("${COMPREPLY[@]#"$prefix"}")
+    fi
+    [[ ${#COMPREPLY[@]} -eq 1 && "${COMPREPLY[0]}" == */ ]] && compopt -o nospace
+}
+complete -o default -F _jam_completions jam
+"##;
+
+/// Zsh
+const SCRIPT_ZSH: &str = r##"#compdef jam
+# jam completions (zsh)
+# Load with: source <(jam completions zsh)
+_jam() {
+    local -a candidates
+    candidates=("${(@f)$(jam __complete $(( CURRENT - 1 )) "${(@)words[2,CURRENT]}" 2>/dev/null)}")
+    candidates=(${candidates:#})
+    compadd -S '' -- ${(M)candidates:#*/}
+    compadd -- ${candidates:#*/}
+}
+compdef _jam jam
+"##;
+
+/// Fish
+const SCRIPT_FISH: &str = r##"# jam completions (fish)
+# Load with: jam completions fish | source
+function __jam_complete
+    set -l tokens (commandline -opc)
+    jam __complete (count $tokens) $tokens[2..-1] (commandline -ct) 2>/dev/null
+end
+complete -c jam -f -a '(__jam_complete)'
+"##;
+
+/// PowerShell (empty arguments are dropped by older versions, so the position is passed instead)
+const SCRIPT_POWERSHELL: &str = r##"# jam completions (PowerShell)
+# Load with: jam completions powershell | Out-String | Invoke-Expression
+Register-ArgumentCompleter -Native -CommandName jam -ScriptBlock {
+    param($wordToComplete, $commandAst, $cursorPosition)
+    $words = @($commandAst.CommandElements |
+        Where-Object { $_.Extent.StartOffset -lt $cursorPosition } |
+        Select-Object -Skip 1 |
+        ForEach-Object { $_.ToString() })
+    $position = if ($wordToComplete -eq '') { $words.Count + 1 } else { $words.Count }
+    & jam __complete $position @words 2>$null | ForEach-Object {
+        [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)
+    }
+}
+"##;
+
+/// Whether the command line asks for completions
+pub fn is_completions_command() -> bool {
+    args().nth(1)
+        .map(|command| command == COMMAND_COMPLETIONS || command == COMMAND_CALLBACK)
+        .unwrap_or(false)
+}
+
+/// Completions entry point
+pub async fn completions_main(workspace: &Workspace) {
+    let Ok(cmd) = CompletionsEntry::try_parse() else {
+        eprintln!("Usage: jam completions <bash|zsh|fish|powershell>");
+        return;
+    };
+
+    match cmd.command {
+        CompletionsCommands::Completions(args) => {
+            print!("{}", match args.shell {
+                CompletionShell::Bash => SCRIPT_BASH,
+                CompletionShell::Zsh => SCRIPT_ZSH,
+                CompletionShell::Fish => SCRIPT_FISH,
+                CompletionShell::Powershell => SCRIPT_POWERSHELL,
+            });
+        }
+        CompletionsCommands::Callback(args) => {
+            if args.position < 1 { return; }
+            let preceding = &args.words[..(args.position - 1).min(args.words.len())];
+            let current = args.words.get(args.position - 1).map(|word| word.as_str()).unwrap_or("");
+            for candidate in complete_words(workspace, preceding, current).await {
+                println!("{}", candidate);
+            }
+        }
+    }
+}
+
+/// Candidates of the current word, after walking the preceding words through the command tree
+async fn complete_words(workspace: &Workspace, preceding: &[String], current: &str) -> Vec<String> {
+    let mut command = match workspace.workspace_type {
+        Client => ClientWorkspaceEntry::command(),
+        Server => ServerWorkspaceEntry::command(),
+        Unknown => WorkspaceSetup::command(),
+    };
+    if let Some(completions) = CompletionsEntry::command().find_subcommand(COMMAND_COMPLETIONS) {
+        command = command.subcommand(completions.clone());
+    }
+    command.build();
+
+    // Step1: Walk the preceding words
+    let mut positionals = 0;
+    let mut pending: Option<Arg> = None;
+    for word in preceding {
+        // Value of the previous option
+        if pending.take().is_some() { continue; }
+
+        if word.starts_with('-') && word.len() > 1 {
+            if let Some(arg) = find_option(&command, word)
+                && arg.get_action().takes_values() && !word.contains('=') {
+                pending = Some(arg.clone());
+            }
+            continue;
+        }
+        if positionals == 0 && let Some(subcommand) = command.find_subcommand(word).cloned() {
+            command = subcommand;
+            continue;
+        }
+        positionals += 1;
+    }
+
+    // Step2: Collect candidates
+    let mut candidates = Vec::new();
+    if let Some(arg) = pending {
+        candidates.extend(complete_value(workspace, &arg, current).await);
+    } else if current.starts_with('-') {
+        candidates.extend(options(&command));
+    } else {
+        if positionals == 0 {
+            candidates.extend(subcommands(&command));
+        }
+        let positional = command.get_positionals().nth(positionals)
+            .or_else(|| command.get_positionals().last()
+                .filter(|arg| arg.get_num_args().map(|range| range.max_values() > 1).unwrap_or(false)));
+        if let Some(arg) = positional {
+            candidates.extend(complete_value(workspace, arg, current).await);
+        }
+    }
+
+    let mut candidates: Vec<String> = candidates.into_iter()
+        .filter(|candidate| candidate.starts_with(current))
+        .collect();
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
+
+/// Option matching "--long", "--long=value" or "-s"
+fn find_option<'a>(command: &'a Command, word: &str) -> Option<&'a Arg> {
+    let word = word.split('=').next().unwrap_or(word);
+    command.get_arguments().find(|arg| {
+        if let Some(long) = word.strip_prefix("--") {
+            arg.get_long() == Some(long) || arg.get_all_aliases().map(|aliases| aliases.contains(&long)).unwrap_or(false)
+        } else {
+            let short = word.trim_start_matches('-');
+            arg.get_short().map(|flag| flag.to_string() == short).unwrap_or(false)
+        }
+    })
+}
+
+/// Visible subcommand names and aliases
+fn subcommands(command: &Command) -> Vec<String> {
+    let mut result = Vec::new();
+    for subcommand in command.get_subcommands() {
+        if subcommand.is_hide_set() { continue; }
+        result.push(subcommand.get_name().to_string());
+        result.extend(subcommand.get_visible_aliases().map(|alias| alias.to_string()));
+    }
+    result
+}
+
+/// Visible flags and options
+fn options(command: &Command) -> Vec<String> {
+    let mut result = Vec::new();
+    for arg in command.get_arguments() {
+        if arg.is_positional() || arg.is_hide_set() { continue; }
+        if let Some(long) = arg.get_long() {
+            result.push(format!("--{}", long));
+        }
+        if let Some(aliases) = arg.get_visible_aliases() {
+            result.extend(aliases.iter().map(|alias| format!("--{}", alias)));
+        }
+        if let Some(short) = arg.get_short() {
+            result.push(format!("-{}", short));
+        }
+    }
+    result
+}
+
+/// Values of an argument: fixed values, member names and duties on the server, searches on the client
+async fn complete_value(workspace: &Workspace, arg: &Arg, current: &str) -> Vec<String> {
+    let possible_values = arg.get_possible_values();
+    if !possible_values.is_empty() {
+        return possible_values.iter()
+            .filter(|value| !value.is_hide_set())
+            .map(|value| value.get_name().to_string())
+            .collect();
+    }
+
+    match (&workspace.workspace_type, arg.get_id().as_str()) {
+        (Server, "member" | "old_name") => workspace.server.as_ref()
+            .map(|server| server.members.values()
+                .map(|member| member.member_name.clone())
+                .collect())
+            .unwrap_or_default(),
+        (Server, "duties") => MemberDuty::iter()
+            .map(|duty| format!("{:?}", duty))
+            .collect(),
+        (Client, _) => complete_search(current).await,
+        _ => Vec::new(),
+    }
+}
+
+/// Virtual paths and local files of the directory, short names (":name") and parameters ("name?")
+pub async fn complete_search(word: &str) -> Vec<String> {
+    let directory = word.rfind('/').map(|index| &word[..=index]).unwrap_or("");
+    let mut result = Vec::new();
+    for item in list_directory(directory, true).await {
+        let replacement = if item.starts_with(':') {
+            if !word.starts_with(':') { continue; }
+            item
+        } else {
+            format!("{}{}", directory, item)
+        };
+        if replacement.starts_with(word) {
+            result.push(replacement);
+        }
+    }
+
+    if !word.is_empty() && directory.is_empty() {
+        for parameter in parameters() {
+            let alias = format!("{}?", parameter);
+            if alias.starts_with(word) {
+                result.push(alias);
+            }
+        }
+    }
+
+    result.sort();
+    result.dedup();
+    result
+}
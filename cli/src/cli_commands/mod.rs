@@ -4,4 +4,6 @@ pub mod server;
 
 pub mod client;
 
+pub mod completions;
+
 mod cli_command_client;
\ No newline at end of file
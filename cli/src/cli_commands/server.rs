@@ -1,14 +1,20 @@
 use std::env::args;
 use crate::data::database::Database;
+use crate::data::database_journal::DatabaseJournal;
 use crate::data::member::{Member, MemberDuty};
+use crate::data::server_hooks::{HookAction, ServerHooks};
 use crate::data::workspace::Workspace;
+use crate::service::jam_hooks::HookLog;
 use crate::service::jam_server::{jam_server_entry, refresh_monitor};
 use crate::service::service_utils::get_self_address;
+use chrono::{Local, TimeZone};
 use clap::{Args, Parser, Subcommand};
+use colored::Colorize;
 use jam_ready::utils::levenshtein_distance::levenshtein_distance;
 use jam_ready::utils::local_archive::LocalArchive;
 use jam_ready::utils::text_process::process_id_text;
 use rand::Rng;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use strum::IntoEnumIterator;
 use tokio::join;
@@ -16,6 +22,9 @@ use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 use tokio::sync::Mutex;
 use crate::help::help_docs::get_help_docs;
 
+/// Deliveries shown by "list delivery"
+const HOOK_LOG_LINES: usize = 20;
+
 /// Server command line interface
 #[derive(Parser, Debug)]
 #[command(
@@ -74,7 +83,10 @@ enum ServerOperationTargetCommands {
     Duty(DutyOperationArgs),
 
     /// Debug level logger
-    Debug
+    Debug,
+
+    /// HTTP/JSON API
+    Http
 }
 
 /// Server list commands
@@ -82,7 +94,13 @@ enum ServerOperationTargetCommands {
 enum ServerListCommands {
 
     /// List members
-    Member
+    Member,
+
+    /// List hooks with their last delivery
+    Hook,
+
+    /// List the latest hook deliveries
+    Delivery
 }
 
 /// Server query commands
@@ -112,6 +130,9 @@ enum ServerSetCommands {
     /// Set member properties
     #[command(subcommand)]
     Member(ServerSetMemberCommands),
+
+    /// Set the address of the HTTP/JSON API
+    Http(HttpAddressArgs),
 }
 
 /// Server set member commands
@@ -155,6 +176,14 @@ struct DutyOperationArgs {
     member: String
 }
 
+/// HTTP/JSON API address arguments
+#[derive(Args, Debug)]
+struct HttpAddressArgs {
+
+    /// Address, e.g. 127.0.0.1:5014 (0.0.0.0:5014 for the whole network)
+    address: String
+}
+
 /// Duties set arguments
 #[derive(Args, Debug)]
 struct DutiesSetArgs {
@@ -192,6 +221,7 @@ pub async fn server_workspace_main() {
                     }
                     Workspace::update(&workspace).await;
                 }
+                ServerOperationTargetCommands::Http => server_enable_http_api(true).await,
             }
         }
         ServerOperationCommands::Remove(op) => {
@@ -205,11 +235,14 @@ pub async fn server_workspace_main() {
                     }
                     Workspace::update(&workspace).await;
                 }
+                ServerOperationTargetCommands::Http => server_enable_http_api(false).await,
             }
         }
         ServerOperationCommands::List(op) => {
             match op {
-                ServerListCommands::Member => server_list_members().await
+                ServerListCommands::Member => server_list_members().await,
+                ServerListCommands::Hook => server_list_hooks().await,
+                ServerListCommands::Delivery => server_list_deliveries().await,
             }
         }
         ServerOperationCommands::Query(op) => {
@@ -229,6 +262,7 @@ pub async fn server_workspace_main() {
                         ServerSetMemberCommands::Name(args) => server_set_member_name(args).await
                     }
                 }
+                ServerSetCommands::Http(args) => server_set_http_address(args.address).await,
             }
         }
     }
@@ -240,8 +274,12 @@ fn server_print_help() {
 
 async fn server_run() {
 
-    // Build database
-    let database = Arc::new(Mutex::new(Database::read().await));
+    // Build database and replay unfolded journal records
+    let mut database = Database::read().await;
+    if DatabaseJournal::replay(&mut database).await > 0 {
+        DatabaseJournal::fold(&database).await;
+    }
+    let database = Arc::new(Mutex::new(database));
 
     // Signals
     let (write_tx, write_rx) : (UnboundedSender<bool>, UnboundedReceiver<bool>) = unbounded_channel();
@@ -249,6 +287,32 @@ async fn server_run() {
     join!(jam_server_entry(database.clone(), write_tx.clone()), refresh_monitor(database.clone(), write_rx));
 }
 
+/// Enable or disable the HTTP/JSON API (takes effect on the next run)
+async fn server_enable_http_api(enable: bool) {
+    let mut workspace = Workspace::read().await;
+    let Some(server) = &mut workspace.server else { return; };
+    server.enable_http_api = enable;
+    if enable {
+        println!("HTTP API enabled at http://{}/api", server.http_address);
+    } else {
+        println!("HTTP API disabled");
+    }
+    Workspace::update(&workspace).await;
+}
+
+/// Set the address of the HTTP/JSON API
+async fn server_set_http_address(address: String) {
+    if address.parse::<SocketAddr>().is_err() {
+        eprintln!("Invalid address: {} (expected IP:PORT)", address);
+        return;
+    }
+    let mut workspace = Workspace::read().await;
+    let Some(server) = &mut workspace.server else { return; };
+    server.http_address = address;
+    println!("HTTP API address set to {}", server.http_address);
+    Workspace::update(&workspace).await;
+}
+
 /// Add member
 async fn server_add_member (member_name: String) {
     let member_name = process_id_text(member_name);
@@ -500,6 +564,46 @@ async fn server_list_members () {
     }
 }
 
+/// List hooks with their last delivery
+async fn server_list_hooks () {
+    let hooks = ServerHooks::read().await.hooks;
+    if hooks.is_empty() {
+        println!("No hooks, add them to {} (see docs/hooks.md)", env!("FILE_SERVER_HOOKS"));
+        return;
+    }
+
+    let deliveries = HookLog::latest(HOOK_LOG_LINES).await;
+    for hook in hooks {
+        let events = if hook.events.is_empty() {
+            "All".to_string()
+        } else {
+            hook.events.iter().map(|event| format!("{:?}", event)).collect::<Vec<_>>().join(", ")
+        };
+        let target = match &hook.action {
+            HookAction::Command(command) => format!("Command: {}", command),
+            HookAction::Post(url) => format!("Post: {}", url),
+        };
+        let last = match deliveries.iter().rev().find(|delivery| delivery.hook == hook.name) {
+            Some(delivery) if delivery.success => format!("last: {}", delivery.detail).green(),
+            Some(delivery) => format!("last: {} (attempt {})", delivery.detail, delivery.attempt).red(),
+            None => "not run yet".normal(),
+        };
+        println!("{} [{}] {} {}", hook.name.cyan(), events, target, last);
+    }
+}
+
+/// List the latest hook deliveries
+async fn server_list_deliveries () {
+    for delivery in HookLog::latest(HOOK_LOG_LINES).await {
+        let time = Local.timestamp_opt(delivery.time as i64, 0)
+            .single()
+            .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        let result = if delivery.success { delivery.detail.green() } else { delivery.detail.red() };
+        println!("{} {} {:?} #{} {}", time, delivery.hook.cyan(), delivery.event, delivery.attempt, result);
+    }
+}
+
 /// Query member duties
 async fn server_query_duties_of_member (member_name: String) {
     let member_name = process_id_text(member_name);
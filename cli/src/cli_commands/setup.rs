@@ -1,21 +1,29 @@
-use crate::cli_commands::client::client_workspace_main;
+use crate::cli_commands::client::{client_workspace_main, print_client_result};
+use crate::cli_commands::completions::{completions_main, is_completions_command};
 use crate::cli_commands::server::server_workspace_main;
 use crate::data::local_file_map::LocalFileMap;
 use crate::data::workspace::WorkspaceType::{Client, Server, Unknown};
-use crate::data::workspace::{ClientWorkspace, ServerWorkspace, Workspace};
+use crate::data::workspace::{default_http_address, ClientWorkspace, ServerWorkspace, Workspace};
 use crate::help::help_docs::get_help_docs;
 use crate::linker::linker::jam_linker_entry;
 use crate::linker::linker_config::LinkerConfig;
-use crate::service::jam_client::search_workspace_lan;
+use crate::service::jam_client::{discover_workspaces_lan, parse_socket_addr, search_workspace_lan, DiscoveredWorkspace};
 use crate::try_correct_current_dir;
 use clap::{Args, Parser, Subcommand};
 use jam_ready::utils::address_str_parser::parse_address_v4_str;
 use jam_ready::utils::hide_folder::hide_folder;
+use jam_ready::utils::levenshtein_distance::levenshtein_distance;
 use jam_ready::utils::local_archive::LocalArchive;
 use jam_ready::utils::text_process::process_id_text_not_to_lower;
 use std::collections::HashMap;
 use std::env::{args, current_dir, current_exe, set_current_dir};
+use std::io::{stdin, stdout, Write};
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use tokio::time::timeout;
+
+/// Time to wait for a server answering its workspace name
+const LEGACY_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(3);
 
 /// Workspace setup entry point
 #[derive(Parser, Debug)]
@@ -25,7 +33,7 @@ use std::net::{IpAddr, Ipv4Addr, SocketAddr};
     disable_help_subcommand = true,
     help_template = "{all-args}"
 )]
-struct WorkspaceSetup {
+pub(crate) struct WorkspaceSetup {
     #[command(subcommand)]
     command: WorkspaceSetupCommands,
 }
@@ -80,6 +88,7 @@ enum LinkerCommands {
     Listen(ListenArgs),
     Port(PortValueArgs),
     SleepMinutes(SleepTimeArgs),
+    EventSyncSeconds(EventSyncArgs),
 }
 
 #[derive(Args, Debug)]
@@ -97,6 +106,11 @@ struct SleepTimeArgs {
     pub minutes: f64
 }
 
+#[derive(Args, Debug)]
+struct EventSyncArgs {
+    pub seconds: f64
+}
+
 /// Setup workspace
 async fn setup_workspace_main(workspace: Workspace) {
     if args().len() <= 1 {
@@ -140,6 +154,11 @@ async fn setup_workspace_main(workspace: Workspace) {
                     println!("Ok! The linker will enter sleep mode after {} minutes of inactivity.", args.minutes);
                     LinkerConfig::update(&linker_config).await;
                 }
+                LinkerCommands::EventSyncSeconds(args) => {
+                    linker_config.event_sync_seconds = args.seconds;
+                    println!("Ok! Subscribed clients are notified of server changes every {} seconds.", args.seconds);
+                    LinkerConfig::update(&linker_config).await;
+                }
             }
         }
     }
@@ -152,36 +171,42 @@ fn setup_print_help() {
 async fn setup_client_workspace(args: ClientSetupArgs, mut workspace: Workspace) {
     workspace.workspace_type = Client;
 
-    // If target address is not specified and workspace name is also not specified, cannot create workspace
-    if args.target.is_none() && args.workspace.is_none() {
-        eprintln!("You need to specify a target or workspace");
-        eprintln!("\"--workspace <NAME>\" or \"--target <ADDRESS>\"");
-        return;
-    }
-
-    // Workspace name
-    let workspace_name =
-        process_id_text_not_to_lower(args.workspace.unwrap_or("Workspace".to_string()));
+    let (workspace_name, target_addr) = if let Some(addr) = args.target {
+        // Known address, resolve via DNS
+        let workspace_name = process_id_text_not_to_lower(args.workspace.unwrap_or("Workspace".to_string()));
+        let addr = parse_address_v4_str(addr).await
+            .unwrap_or(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 5011));
+        (workspace_name, addr)
+    } else {
+        // Unknown, try network discovery
+        let found = discover_workspaces_lan().await.unwrap_or_default();
+        match (pick_workspace(&found, args.workspace.as_deref()), args.workspace) {
+            (Some(picked), _) => (process_id_text_not_to_lower(picked.name.clone()), parse_socket_addr(&picked.address).unwrap()),
+
+            // Declined, or nothing to pick from
+            (None, None) => return,
+            (None, Some(_)) if !found.is_empty() => return,
+
+            // Servers of older versions only answer their exact name
+            (None, Some(name)) => {
+                let workspace_name = process_id_text_not_to_lower(name);
+                let addr = match timeout(LEGACY_DISCOVERY_TIMEOUT, search_workspace_lan(workspace_name.clone())).await {
+                    Ok(Ok(addr)) => addr,
+
+                    // Fallback to default address
+                    _ => SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 5011)
+                };
+                (workspace_name, addr)
+            }
+        }
+    };
 
     let client = ClientWorkspace {
         // Workspace name
-        workspace_name: workspace_name.clone(),
+        workspace_name,
 
         // Target address
-        target_addr: if let Some(addr) = args.target {
-            // Known address, resolve via DNS
-            parse_address_v4_str(addr).await
-                .unwrap_or(SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 5011))
-        } else {
-            // Unknown, try network discovery
-            let addr = search_workspace_lan(workspace_name).await;
-            if let Ok(addr) = addr {
-                addr
-            } else {
-                // Fallback to default address
-                SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 5011)
-            }
-        },
+        target_addr,
 
         // Login code
         login_code: args.login_code.trim().to_string(),
@@ -189,6 +214,10 @@ async fn setup_client_workspace(args: ClientSetupArgs, mut workspace: Workspace)
         uuid: "".to_string(),
 
         debug: args.debug,
+
+        sparse_include: Vec::new(),
+
+        sparse_exclude: Vec::new(),
     };
     workspace.client = Some(client);
 
@@ -205,6 +234,69 @@ async fn setup_client_workspace(args: ClientSetupArgs, mut workspace: Workspace)
     let _ = hide_folder(&jam_folder);
 }
 
+/// Pick a discovered workspace
+/// With a name, the exact match is used, otherwise the closest name is suggested
+/// Without a name, the user selects one from the list
+fn pick_workspace<'a>(found: &'a [DiscoveredWorkspace], name: Option<&str>) -> Option<&'a DiscoveredWorkspace> {
+    let Some(name) = name else {
+        if found.is_empty() {
+            eprintln!("No workspace found on the local network");
+            eprintln!("Use \"--target <ADDRESS>\" to connect directly");
+            return None;
+        }
+        return select_workspace(found);
+    };
+
+    let name = name.trim();
+    if let Some(exact) = found.iter().find(|info| info.name == name) {
+        return Some(exact);
+    }
+    if found.is_empty() {
+        return None;
+    }
+
+    // Closest name (case insensitive)
+    let (nearest, dist) = found.iter()
+        .map(|info| (info, levenshtein_distance(&info.name.to_lowercase(), &name.to_lowercase())))
+        .min_by_key(|(_, dist)| *dist)?;
+    if dist < 3 {
+        let answer = prompt(&format!("Unable to find a workspace named \"{}\". Did you mean \"{}\" ({})? [Y/n] ",
+                                     name, nearest.name, nearest.address));
+        return match answer.to_lowercase().as_str() {
+            "" | "y" | "yes" => Some(nearest),
+            _ => None,
+        };
+    }
+
+    eprintln!("Unable to find a workspace named \"{}\"", name);
+    select_workspace(found)
+}
+
+/// List discovered workspaces and let the user select one
+fn select_workspace(found: &[DiscoveredWorkspace]) -> Option<&DiscoveredWorkspace> {
+    println!("Workspaces on the local network:");
+    for (index, info) in found.iter().enumerate() {
+        println!("  {}. {}  v{}  {} member(s)  {}", index + 1, info.name, info.version, info.members, info.address);
+    }
+    let answer = prompt(&format!("Select a workspace [1-{}]: ", found.len()));
+    let picked = answer.parse::<usize>().ok()
+        .and_then(|index| index.checked_sub(1))
+        .and_then(|index| found.get(index));
+    if picked.is_none() {
+        eprintln!("No workspace selected");
+    }
+    picked
+}
+
+/// Ask a question on the terminal, returns the trimmed answer
+fn prompt(question: &str) -> String {
+    print!("{}", question);
+    let _ = stdout().flush();
+    let mut answer = String::new();
+    let _ = stdin().read_line(&mut answer);
+    answer.trim().to_string()
+}
+
 async fn setup_server_workspace(args: ServerSetupArgs, mut workspace: Workspace) {
     workspace.workspace_type = Server;
     let server = ServerWorkspace {
@@ -215,6 +307,8 @@ async fn setup_server_workspace(args: ServerSetupArgs, mut workspace: Workspace)
         member_uuids: HashMap::new(),
         login_code_map: HashMap::new(),
         enable_debug_logger: true,
+        enable_http_api: false,
+        http_address: default_http_address(),
     };
     workspace.server = Some(server);
 
@@ -236,16 +330,17 @@ pub async fn cli_entry() {
     #[cfg(windows)]
     colored::control::set_virtual_terminal(true).unwrap();
 
+    // Shell completions work in every workspace
+    if is_completions_command() {
+        completions_main(&workspace).await;
+        return;
+    }
+
     // If workspace is not initialized, guide user through setup
     if workspace.workspace_type == Unknown {
         setup_workspace_main(workspace).await;
     } else if workspace.workspace_type == Client {
-        match client_workspace_main(args()).await {
-            None => {}
-            Some(result) => {
-                result.end_print();
-            }
-        }
+        print_client_result(client_workspace_main(args()).await);
     } else if workspace.workspace_type == Server {
         server_workspace_main().await;
     }
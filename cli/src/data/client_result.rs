@@ -1,12 +1,38 @@
 use crate::data::client_result::ClientResultType::Fail;
 use crate::data::workspace::Workspace;
+use clap::ValueEnum;
 use colored::Colorize;
 use jam_ready::utils::local_archive::LocalArchive;
 use jam_ready::utils::text_process::{process_id_text, process_text};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 use tokio::time::Instant;
 
+/// Version of the "--output json" document, raised when fields change
+const JSON_OUTPUT_VERSION: u32 = 1;
+
+/// Output format selected by "--output"
+static OUTPUT_JSON: AtomicBool = AtomicBool::new(false);
+
+/// Results ended during the command, printed as one document by print_json_output
+static JSON_OUTPUT: Mutex<Option<JsonOutput>> = Mutex::new(None);
+
+/// Errors reported outside a result (connection, authentication)
+static PENDING_ERRORS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Output format of client commands
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Default)]
+pub enum OutputFormat {
+    /// Colored text for the terminal
+    #[default]
+    Text,
+
+    /// One JSON document per command
+    Json,
+}
+
 #[derive(Serialize, Clone, Debug)]
 pub struct ClientResult {
     // All messages
@@ -73,8 +99,92 @@ pub struct QueryResult {
     metadata: HashMap<String, String>,
 }
 
+/// Document printed by "--output json", every field is always present
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct JsonOutput {
+    /// Document version
+    #[serde(rename = "Version")]
+    version: u32,
+
+    /// Result type (Query, Success or Fail)
+    #[serde(rename = "ResultType")]
+    result_type: ClientResultType,
+
+    /// Query results
+    #[serde(rename = "Query")]
+    query: Vec<String>,
+
+    /// Normal messages
+    #[serde(rename = "LogMsg")]
+    log_msg: Vec<String>,
+
+    /// Warning messages
+    #[serde(rename = "WarnMsg")]
+    warn_msg: Vec<String>,
+
+    /// Error messages
+    #[serde(rename = "ErrMsg")]
+    err_msg: Vec<String>,
+
+    /// Metadata
+    #[serde(rename = "Metadata")]
+    metadata: HashMap<String, String>,
+
+    /// Elapsed time
+    #[serde(rename = "ElapsedSeconds")]
+    elapsed_secs: f64,
+}
+
+/// Select the output format of the following results
+pub fn set_output_format(format: OutputFormat) {
+    OUTPUT_JSON.store(format == OutputFormat::Json, Ordering::Relaxed);
+}
+
+/// Check if results are printed as JSON
+pub fn output_json() -> bool {
+    OUTPUT_JSON.load(Ordering::Relaxed)
+}
+
+/// Report an error that does not belong to a result (printed immediately in text mode)
+pub fn report_err(msg: &str) {
+    if output_json() {
+        PENDING_ERRORS.lock().unwrap().push(strip_ansi_escapes::strip_str(msg).trim().to_string());
+    } else {
+        eprintln!("{}", msg);
+    }
+}
+
+/// Print the results of the command as exactly one JSON document
+pub fn print_json_output() {
+    println!("{}", serde_json::to_string(&take_json_output()).unwrap_or("{}".to_string()));
+}
+
+/// Take the results collected since the last call as one document
+pub fn take_json_output() -> JsonOutput {
+    let mut output = JSON_OUTPUT.lock().unwrap().take().unwrap_or(JsonOutput {
+        version: JSON_OUTPUT_VERSION,
+        result_type: ClientResultType::Success,
+        query: vec![],
+        log_msg: vec![],
+        warn_msg: vec![],
+        err_msg: vec![],
+        metadata: Default::default(),
+        elapsed_secs: 0.0,
+    });
+
+    let pending: Vec<String> = PENDING_ERRORS.lock().unwrap().drain(..).collect();
+    if !pending.is_empty() {
+        output.err_msg.extend(pending);
+        output.result_type = Fail;
+    }
+    output
+}
+
 impl ClientResult {
     pub async fn debug_mode() -> bool {
+        if output_json() {
+            return true;
+        }
         let workspace = Workspace::read().await;
         if let Some(client) = workspace.client {
             client.debug
@@ -116,7 +226,9 @@ impl ClientResult {
             println!("Log: {}", &msg);
         }
         self.log_msg.push(
-            if self.debug {
+            if output_json() {
+                strip_ansi_escapes::strip_str(msg).trim().to_string()
+            } else if self.debug {
                 strip_ansi_escapes::strip_str(
                     process_text(msg.to_string())
                 )
@@ -131,9 +243,13 @@ impl ClientResult {
             println!("{}", format!("Warn: {}", &msg).bright_yellow());
         }
         self.warn_msg.push(
-            strip_ansi_escapes::strip_str(
-                process_text(msg.to_string())
-            )
+            if output_json() {
+                strip_ansi_escapes::strip_str(msg).trim().to_string()
+            } else {
+                strip_ansi_escapes::strip_str(
+                    process_text(msg.to_string())
+                )
+            }
         );
     }
 
@@ -142,9 +258,13 @@ impl ClientResult {
             println!("{}", format!("Err: {}", &msg).bright_red());
         }
         self.err_msg.push(
-            strip_ansi_escapes::strip_str(
-                process_text(msg.to_string())
-            )
+            if output_json() {
+                strip_ansi_escapes::strip_str(msg).trim().to_string()
+            } else {
+                strip_ansi_escapes::strip_str(
+                    process_text(msg.to_string())
+                )
+            }
         );
         if self.result_type != ClientResultType::Query {
             self.result_type = Fail;
@@ -152,7 +272,12 @@ impl ClientResult {
     }
 
     pub fn metadata(&mut self, data_key: String, data_val: String) {
-        self.metadata.insert(process_id_text(data_key), process_text(data_val));
+        let data_val = if output_json() { data_val.trim().to_string() } else { process_text(data_val) };
+        self.metadata.insert(process_id_text(data_key), data_val);
+    }
+
+    pub fn get_metadata(&self, data_key: &str) -> Option<&String> {
+        self.metadata.get(&process_id_text(data_key.to_string()))
     }
 
     /// The function will print a message and return a string in debug mode,
@@ -162,6 +287,18 @@ impl ClientResult {
         // Computing elapsed time
         self.elapsed_secs = self.start_instant.elapsed().as_secs_f64();
 
+        // JSON output, collected until the command ends
+        if output_json() {
+            let output = JsonOutput::from(self);
+            let result = serde_json::to_string(&output).unwrap_or("{}".to_string());
+            let mut collected = JSON_OUTPUT.lock().unwrap();
+            match collected.as_mut() {
+                Some(collected) => collected.merge(output),
+                None => *collected = Some(output),
+            }
+            return result;
+        }
+
         // Debug output, serialize directly
         if self.debug {
             if self.result_type == ClientResultType::Query {
@@ -296,6 +433,41 @@ impl ClientResultQueryProcess {
     }
 }
 
+impl JsonOutput {
+    /// Merge a result ended later in the same command
+    fn merge(&mut self, other: JsonOutput) {
+        if self.result_type != Fail {
+            self.result_type = other.result_type;
+        }
+        self.query.extend(other.query);
+        self.log_msg.extend(other.log_msg);
+        self.warn_msg.extend(other.warn_msg);
+        self.err_msg.extend(other.err_msg);
+        self.metadata.extend(other.metadata);
+        self.elapsed_secs += other.elapsed_secs;
+    }
+}
+
+impl From<ClientResult> for JsonOutput {
+    fn from(value: ClientResult) -> Self {
+        let (query, log_msg) = if value.result_type == ClientResultType::Query {
+            (value.log_msg, vec![])
+        } else {
+            (vec![], value.log_msg)
+        };
+        Self {
+            version: JSON_OUTPUT_VERSION,
+            result_type: value.result_type,
+            query,
+            log_msg,
+            warn_msg: value.warn_msg,
+            err_msg: value.err_msg,
+            metadata: value.metadata,
+            elapsed_secs: value.elapsed_secs,
+        }
+    }
+}
+
 impl From<ClientResult> for QueryResult {
     fn from(value: ClientResult) -> Self {
         Self {
@@ -20,7 +20,36 @@ pub struct Database {
 
     /// File path to Uuid mapping (Path, Uuid)
     #[serde(rename = "uuids")]
-    virtual_uuids: HashMap<String, String>
+    virtual_uuids: HashMap<String, String>,
+
+    /// Committed changesets (Id, Changeset)
+    #[serde(rename = "changesets", default)]
+    changesets: HashMap<String, Changeset>
+}
+
+/// Changeset
+/// A group of file versions committed together, which can be rolled back as a unit
+#[derive(Serialize, Deserialize, Encode, Decode, Clone, Debug, PartialEq)]
+pub struct Changeset {
+    /// Commit message
+    #[serde(rename = "message")]
+    pub message: String,
+
+    /// Committer name
+    #[serde(rename = "member")]
+    pub member: String,
+
+    /// Unix time (seconds)
+    #[serde(rename = "time")]
+    pub time: u64,
+
+    /// Version changes (Uuid, (Version before, Version after))
+    #[serde(rename = "files")]
+    pub files: HashMap<String, (u32, u32)>,
+
+    /// Whether the changeset has been rolled back
+    #[serde(rename = "reverted", default)]
+    pub reverted: bool
 }
 
 /// Virtual File
@@ -53,7 +82,15 @@ pub struct VirtualFile {
 
     /// Whether the lock is long-term
     #[serde(rename = "long")]
-    longer_lock: bool
+    longer_lock: bool,
+
+    /// Whether commits without a lock are merged (text files only)
+    #[serde(rename = "mergeable", default)]
+    mergeable: bool,
+
+    /// Content digest of each version, recorded on commit
+    #[serde(rename = "digests", default)]
+    digest_histories: HashMap<u32, String>
 }
 
 /// Virtual File Status
@@ -71,7 +108,8 @@ impl Default for Database {
     fn default() -> Self {
         Self {
             virtual_files: HashMap::new(),
-            virtual_uuids: HashMap::new()
+            virtual_uuids: HashMap::new(),
+            changesets: HashMap::new()
         }
     }
 }
@@ -279,6 +317,108 @@ impl Database {
         }
         None
     }
+
+    /// Get changeset by Id
+    pub fn changeset(&self, id: &str) -> Option<&Changeset> {
+        self.changesets.get(id.trim())
+    }
+
+    /// Get all changesets, oldest first (Id, Changeset)
+    pub fn changesets(&self) -> Vec<(&String, &Changeset)> {
+        let mut changesets: Vec<_> = self.changesets.iter().collect();
+        changesets.sort_by(|(a_id, a), (b_id, b)| a.time.cmp(&b.time).then(a_id.cmp(b_id)));
+        changesets
+    }
+
+    /// Insert changeset, returns its new Id
+    pub fn insert_changeset(&mut self, changeset: Changeset) -> String {
+        let id = loop {
+            let id = Uuid::new_v4().simple().to_string()[..8].to_string();
+            if !self.changesets.contains_key(&id) {
+                break id;
+            }
+        };
+        self.changesets.insert(id.clone(), changeset);
+        id
+    }
+
+    /// Roll back all files of a changeset to their previous versions
+    /// Every file must still be at the committed version and held by the member
+    pub fn rollback_changeset(&mut self, id: &str, member_uuid: &str) -> Result<usize, String> {
+        let Some(changeset) = self.changesets.get(id.trim()) else {
+            return Err(format!("Changeset '{}' not found", id.trim()));
+        };
+        if changeset.reverted {
+            return Err(format!("Changeset '{}' has already been rolled back", id.trim()));
+        }
+
+        // Check all files first, so the changeset is never half rolled back
+        for (uuid, (_, after)) in &changeset.files {
+            let Some(file) = self.virtual_files.get(uuid) else {
+                return Err(format!("File '{}' not found", uuid));
+            };
+            if file.version != *after {
+                return Err(format!("File '{}' has changed since this changeset", file.path));
+            }
+            if file.get_locker_owner_uuid().as_deref() != Some(member_uuid.trim()) {
+                return Err(format!("Acquire lock of '{}' before rollback", file.path));
+            }
+        }
+
+        // Rollback
+        let files = changeset.files.clone();
+        for (uuid, (before, _)) in &files {
+            if let Some(file) = self.virtual_files.get_mut(uuid) {
+                file.rollback_to_version(*before);
+            }
+        }
+        if let Some(changeset) = self.changesets.get_mut(id.trim()) {
+            changeset.reverted = true;
+        }
+        Ok(files.len())
+    }
+
+    /// Collect changesets that differ from a snapshot (Id, Changeset)
+    pub fn changed_changesets(&self, snapshot: &Database) -> HashMap<String, Changeset> {
+        let mut changed = HashMap::new();
+        for (id, changeset) in self.changesets.iter() {
+            if snapshot.changesets.get(id) != Some(changeset) {
+                changed.insert(id.clone(), changeset.clone());
+            }
+        }
+        changed
+    }
+
+    /// Overwrite the state of a changeset by Id
+    pub fn put_changeset(&mut self, id: String, changeset: Changeset) {
+        self.changesets.insert(id, changeset);
+    }
+
+    /// Collect files that differ from a snapshot (Uuid, File)
+    pub fn changed_files(&self, snapshot: &Database) -> HashMap<String, VirtualFile> {
+        let mut changed = HashMap::new();
+        for (uuid, file) in self.virtual_files.iter() {
+            if snapshot.virtual_files.get(uuid) != Some(file) {
+                changed.insert(uuid.clone(), file.clone());
+            }
+        }
+        changed
+    }
+
+    /// Overwrite the state of a file by Uuid and rebuild its path mapping
+    pub fn put_virtual_file(&mut self, uuid: String, file: VirtualFile) {
+        // Remove old path mapping
+        if let Some(old) = self.virtual_files.get(&uuid)
+            && self.virtual_uuids.get(&old.path) == Some(&uuid) {
+            self.virtual_uuids.remove(&old.path);
+        }
+
+        // Rebuild path mapping (files without path are removed files)
+        if !file.path.trim().is_empty() {
+            self.virtual_uuids.insert(file.path.clone(), uuid.clone());
+        }
+        self.virtual_files.insert(uuid, file);
+    }
 }
 
 impl VirtualFile {
@@ -291,7 +431,9 @@ impl VirtualFile {
             change_histories: Default::default(),
             real_histories: Default::default(),
             state: Available,
-            longer_lock: false
+            longer_lock: false,
+            mergeable: false,
+            digest_histories: Default::default()
         };
 
         // Add version 0 data
@@ -371,6 +513,15 @@ impl VirtualFile {
         }
     }
 
+    /// Get change histories, oldest first (Version, Info)
+    pub fn change_histories(&self) -> Vec<(u32, String)> {
+        let mut histories: Vec<_> = self.change_histories.iter()
+            .map(|(version, info)| (*version, info.clone()))
+            .collect();
+        histories.sort_by_key(|(version, _)| *version);
+        histories
+    }
+
     /// Get real path for specific version
     pub fn real_path_version(&self, version: u32) -> Option<PathBuf> {
         if let Some(real) = self.real_histories.get(&version) {
@@ -380,6 +531,16 @@ impl VirtualFile {
         None
     }
 
+    /// Get content digest for specific version (unknown for versions before digests were recorded)
+    pub fn version_digest(&self, version: u32) -> Option<String> {
+        self.digest_histories.get(&version).cloned()
+    }
+
+    /// Record content digest for specific version
+    pub fn set_version_digest(&mut self, version: u32, digest: String) {
+        self.digest_histories.insert(version, digest);
+    }
+
     /// Update real path
     pub fn update(&mut self, new_real_path: String, changes_info: String) {
         // Increment version
@@ -499,4 +660,19 @@ impl VirtualFile {
     pub fn is_longer_lock_unchecked(&self) -> bool {
         self.longer_lock
     }
+
+    /// Check if the file accepts merged commits
+    pub fn is_mergeable(&self) -> bool {
+        self.mergeable
+    }
+
+    /// Check if the file can be committed without a lock (mergeable and not locked by anyone)
+    pub fn is_mergeable_unlocked(&self) -> bool {
+        self.mergeable && self.state == Available
+    }
+
+    /// Set mergeable mode
+    pub fn set_mergeable(&mut self, mergeable: bool) {
+        self.mergeable = mergeable;
+    }
 }
\ No newline at end of file
@@ -0,0 +1,153 @@
+use crate::data::database::{Changeset, Database, VirtualFile};
+use jam_ready::utils::local_archive::LocalArchive;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env::current_dir;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs;
+use tokio::fs::OpenOptions;
+use tokio::io::AsyncWriteExt;
+
+/// # Database Journal - Write-Ahead Journal
+/// Every acknowledged database change is appended here before the client receives its answer,
+/// and folded into the main database periodically.
+/// Each line is one record, so a torn write can only damage the last line.
+pub struct DatabaseJournal;
+
+/// Journal Record
+/// Stores the full state of every file touched by one operation, so replaying it twice is harmless
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct JournalRecord {
+    /// Unix time (seconds)
+    #[serde(rename = "time")]
+    pub time: u64,
+
+    /// Member Uuid who caused the change
+    #[serde(rename = "member")]
+    pub member: String,
+
+    /// Changed files (Uuid, File)
+    #[serde(rename = "files")]
+    pub files: HashMap<String, VirtualFile>,
+
+    /// Changed changesets (Id, Changeset)
+    #[serde(rename = "changesets", default)]
+    pub changesets: HashMap<String, Changeset>,
+}
+
+impl DatabaseJournal {
+    /// Journal file path
+    pub fn path() -> Option<PathBuf> {
+        current_dir().ok().map(|current| current.join(env!("FILE_DATABASE_JOURNAL")))
+    }
+
+    /// Write changes made since the snapshot to the journal
+    /// Falls back to a full database save if the journal cannot be written
+    pub async fn write(snapshot: &Database, database: &Database, member_uuid: &str) {
+        let files = database.changed_files(snapshot);
+        let changesets = database.changed_changesets(snapshot);
+        if files.is_empty() && changesets.is_empty() {
+            return;
+        }
+
+        let record = JournalRecord {
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            member: member_uuid.to_string(),
+            files,
+            changesets,
+        };
+
+        if let Err(err) = Self::append(&record).await {
+            error!("Failed to write journal: {}, saving database directly", err);
+            Database::update(database).await;
+        }
+    }
+
+    /// Append one record and flush it to disk
+    async fn append(record: &JournalRecord) -> std::io::Result<()> {
+        let Some(path) = Self::path() else {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Journal path not found"));
+        };
+
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        file.write_all(line.as_bytes()).await?;
+        file.sync_data().await?;
+        Ok(())
+    }
+
+    /// Read all complete records
+    async fn records() -> Vec<JournalRecord> {
+        let Some(path) = Self::path() else { return Vec::new(); };
+        let Ok(content) = fs::read_to_string(path).await else { return Vec::new(); };
+
+        let mut records = Vec::new();
+        for line in content.lines() {
+            if line.trim().is_empty() { continue; }
+            match serde_json::from_str::<JournalRecord>(line) {
+                Ok(record) => records.push(record),
+                Err(_) => {
+                    // Torn write, the operation was never acknowledged
+                    warn!("Skipped an incomplete journal record");
+                }
+            }
+        }
+        records
+    }
+
+    /// Check if the journal has unfolded records
+    pub async fn is_empty() -> bool {
+        let Some(path) = Self::path() else { return true; };
+        match fs::metadata(path).await {
+            Ok(metadata) => metadata.len() == 0,
+            Err(_) => true
+        }
+    }
+
+    /// Replay all records onto the database, returns the record count
+    pub async fn replay(database: &mut Database) -> usize {
+        let records = Self::records().await;
+        for record in &records {
+            for (uuid, file) in record.files.clone() {
+                database.put_virtual_file(uuid, file);
+            }
+            for (id, changeset) in record.changesets.clone() {
+                database.put_changeset(id, changeset);
+            }
+        }
+        if !records.is_empty() {
+            info!("Replayed {} journal record(s)", records.len());
+        }
+        records.len()
+    }
+
+    /// Fold the journal into the main database
+    /// Must be called while holding the database lock, so no record is appended in between
+    pub async fn fold(database: &Database) {
+        let (Ok(current), Some(journal)) = (current_dir(), Self::path()) else { return; };
+
+        // Save to a temp file and swap it in, so a crash never leaves a half-written database
+        let temp = format!("{}.tmp", env!("FILE_DATABASE"));
+        Database::update_to(database, temp.clone()).await;
+        if let Err(err) = fs::rename(current.join(&temp), current.join(env!("FILE_DATABASE"))).await {
+            error!("Failed to fold journal: {}", err);
+            return;
+        }
+
+        // Records are now in the database, truncate the journal
+        if let Err(err) = fs::write(journal, "").await {
+            error!("Failed to truncate journal: {}", err);
+        }
+    }
+}
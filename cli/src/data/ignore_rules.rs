@@ -0,0 +1,308 @@
+use jam_ready::utils::text_process::process_path_text;
+use regex::Regex;
+use std::env::current_dir;
+use std::fs;
+use std::path::Path;
+
+/// Name of ignore files, read from the workspace root and from subdirectories
+pub const IGNORE_FILE_NAME: &str = ".jamignore";
+
+/// Built-in rules, applied before all ignore files (can be negated with "!")
+const DEFAULT_RULES: [&str; 17] = [
+    // Version control and editors
+    ".git/", ".svn/", ".vs/", ".vscode/", ".idea/",
+
+    // Unity
+    "Library/", "Temp/", "Logs/", "obj/", "UserSettings/",
+
+    // Godot
+    ".godot/", ".import/",
+
+    // Build outputs and system files
+    "*.tmp", "*.log", ".DS_Store", "Thumbs.db", "desktop.ini",
+];
+
+/// Ignore Rules
+/// Gitignore-style rules deciding which local files are left out when scanning the workspace
+pub struct IgnoreRules {
+    rules: Vec<IgnoreRule>,
+}
+
+/// A single rule
+struct IgnoreRule {
+    /// Directory of the ignore file ("" or "Art/")
+    base: String,
+
+    /// Compiled pattern, matched against the path relative to the base
+    pattern: Regex,
+
+    /// "!" rule, re-includes matched paths
+    negate: bool,
+
+    /// "name/" rule, only matches directories
+    dir_only: bool,
+}
+
+impl Default for IgnoreRules {
+    /// Built-in rules only
+    fn default() -> Self {
+        let mut rules = Self { rules: Vec::new() };
+        for line in DEFAULT_RULES {
+            rules.push_line("", line);
+        }
+        rules
+    }
+}
+
+impl IgnoreRules {
+    /// Load built-in rules and all ignore files of the workspace
+    /// Ignored directories are not searched for further ignore files
+    pub fn load() -> Self {
+        let mut rules = Self::default();
+        if let Ok(current) = current_dir() {
+            rules.load_directory(&current, "");
+        }
+        rules
+    }
+
+    /// Load the ignore file of a directory, then its subdirectories
+    fn load_directory(&mut self, root: &Path, relative: &str) {
+        let directory = root.join(relative);
+        if let Ok(content) = fs::read_to_string(directory.join(IGNORE_FILE_NAME)) {
+            for line in content.lines() {
+                self.push_line(relative, line);
+            }
+        }
+
+        let Ok(entries) = fs::read_dir(&directory) else { return; };
+        for entry in entries.filter_map(Result::ok) {
+            if !entry.file_type().map(|file_type| file_type.is_dir()).unwrap_or(false) {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            let child = format!("{}{}/", relative, name);
+            if !self.is_ignored_dir(&child) {
+                self.load_directory(root, &child);
+            }
+        }
+    }
+
+    /// Parse one line of an ignore file
+    fn push_line(&mut self, base: &str, line: &str) {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return;
+        }
+
+        let (negate, line) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, line) = match line.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, line),
+        };
+
+        // Patterns containing "/" are relative to the ignore file, others match at any depth
+        let anchored = line.contains('/');
+        let line = line.trim_start_matches('/');
+        if line.is_empty() {
+            return;
+        }
+
+        let body = glob_to_regex(line);
+        let expression = if anchored {
+            format!("^{}$", body)
+        } else {
+            format!("^(?:.*/)?{}$", body)
+        };
+
+        if let Ok(pattern) = Regex::new(&expression) {
+            self.rules.push(IgnoreRule {
+                base: base.to_string(),
+                pattern,
+                negate,
+                dir_only,
+            });
+        }
+    }
+
+    /// Check if a relative file path is ignored
+    pub fn is_ignored(&self, path: &str) -> bool {
+        self.is_ignored_path(path, false)
+    }
+
+    /// Check if a relative directory path is ignored
+    pub fn is_ignored_dir(&self, path: &str) -> bool {
+        self.is_ignored_path(path, true)
+    }
+
+    fn is_ignored_path(&self, path: &str, is_dir: bool) -> bool {
+        let path = process_path_text(path.to_string());
+        let path = path.trim_start_matches("./").trim_end_matches('/');
+
+        // Workspace data is never tracked
+        if format!("{}/", path).starts_with(env!("PATH_WORKSPACE_ROOT")) {
+            return true;
+        }
+
+        // A file inside an ignored directory is always ignored
+        let parts: Vec<&str> = path.split('/').collect();
+        for end in 1..parts.len() {
+            if self.last_match(&parts[..end].join("/"), true) == Some(true) {
+                return true;
+            }
+        }
+
+        self.last_match(path, is_dir).unwrap_or(false)
+    }
+
+    /// The last matching rule decides
+    fn last_match(&self, path: &str, is_dir: bool) -> Option<bool> {
+        let mut result = None;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let Some(relative) = path.strip_prefix(rule.base.as_str()) else { continue; };
+            if rule.pattern.is_match(relative) {
+                result = Some(!rule.negate);
+            }
+        }
+        result
+    }
+}
+
+/// Normalize the separators of a glob on virtual paths ("./Art\\*.png" -> "Art/*.png")
+/// Unlike process_path_text, wildcards are kept
+pub fn normalize_glob(glob: &str) -> String {
+    glob.trim()
+        .replace('\\', "/")
+        .trim_start_matches("./")
+        .trim_start_matches('/')
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Convert a glob to a regular expression ("**" crosses directories, "*" and "?" do not)
+/// "{a,b}" matches one of the alternatives
+pub fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut regex = String::new();
+    let mut index = 0;
+    while index < chars.len() {
+        match chars[index] {
+            '*' if chars.get(index + 1) == Some(&'*') => {
+                if chars.get(index + 2) == Some(&'/') {
+                    regex.push_str("(?:.*/)?");
+                    index += 3;
+                } else {
+                    regex.push_str(".*");
+                    index += 2;
+                }
+                continue;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                match chars[index..].iter().position(|c| *c == ']') {
+                    Some(end) if end > 1 => {
+                        let class: String = chars[index + 1..index + end].iter().collect();
+                        let class = class.strip_prefix('!').map(|rest| format!("^{}", rest)).unwrap_or(class);
+                        regex.push_str(&format!("[{}]", class.replace('\\', "\\\\")));
+                        index += end + 1;
+                        continue;
+                    }
+                    _ => regex.push_str("\\["),
+                }
+            }
+            '{' => {
+                match chars[index..].iter().position(|c| *c == '}') {
+                    Some(end) if end > 1 => {
+                        let alternatives: String = chars[index + 1..index + end].iter().collect();
+                        let alternatives: Vec<String> = alternatives.split(',').map(glob_to_regex).collect();
+                        regex.push_str(&format!("(?:{})", alternatives.join("|")));
+                        index += end + 1;
+                        continue;
+                    }
+                    _ => regex.push_str("\\{"),
+                }
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        index += 1;
+    }
+    regex
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(lines: &[(&str, &str)]) -> IgnoreRules {
+        let mut rules = IgnoreRules { rules: Vec::new() };
+        for (base, line) in lines {
+            rules.push_line(base, line);
+        }
+        rules
+    }
+
+    #[test]
+    fn anchored_and_unanchored_rules() {
+        let rules = parse(&[("", "*.psd"), ("", "/Build/out.txt"), ("Art/", "Raw/*.png")]);
+        assert!(rules.is_ignored("Art/Hero/hero.psd"));
+        assert!(rules.is_ignored("Build/out.txt"));
+        assert!(!rules.is_ignored("Game/Build/out.txt"));
+        assert!(rules.is_ignored("Art/Raw/hero.png"));
+        assert!(!rules.is_ignored("Raw/hero.png"));
+        assert!(!rules.is_ignored("Art/Hero/Raw/hero.png"));
+    }
+
+    #[test]
+    fn dir_only_rules_skip_files() {
+        let rules = parse(&[("", "Cache/")]);
+        assert!(rules.is_ignored_dir("Cache"));
+        assert!(rules.is_ignored_dir("Game/Cache/"));
+        assert!(!rules.is_ignored("Cache"));
+    }
+
+    #[test]
+    fn files_inside_ignored_directories() {
+        let rules = parse(&[("", "Cache/"), ("", "!Cache/keep.txt")]);
+        assert!(rules.is_ignored("Cache/data.bin"));
+        assert!(rules.is_ignored("Game/Cache/Deep/data.bin"));
+        // Like git, files cannot be re-included once their directory is ignored
+        assert!(rules.is_ignored("Cache/keep.txt"));
+    }
+
+    #[test]
+    fn negated_rules_re_include() {
+        let rules = parse(&[("", "*.log"), ("", "!important.log")]);
+        assert!(rules.is_ignored("debug.log"));
+        assert!(!rules.is_ignored("Logs/important.log"));
+
+        // The last matching rule decides
+        let rules = parse(&[("", "!important.log"), ("", "*.log")]);
+        assert!(rules.is_ignored("important.log"));
+    }
+
+    #[test]
+    fn glob_syntax() {
+        let rules = parse(&[("", "Art/**/*.tmp"), ("", "v[!0-9].txt"), ("", "*.{bak,orig}")]);
+        assert!(rules.is_ignored("Art/a.tmp"));
+        assert!(rules.is_ignored("Art/Hero/Deep/a.tmp"));
+        assert!(!rules.is_ignored("Audio/a.tmp"));
+        assert!(rules.is_ignored("vx.txt"));
+        assert!(!rules.is_ignored("v1.txt"));
+        assert!(rules.is_ignored("Scene.bak"));
+        assert!(rules.is_ignored("Scene.orig"));
+        assert!(!rules.is_ignored("Scene.unity"));
+    }
+
+    #[test]
+    fn normalizes_globs_keeping_wildcards() {
+        assert_eq!(normalize_glob(" ./Art\\*.png "), "Art/*.png");
+        assert_eq!(normalize_glob("/Art/**/"), "Art/**");
+        assert_eq!(normalize_glob("Art/{a,b}?.png"), "Art/{a,b}?.png");
+    }
+}
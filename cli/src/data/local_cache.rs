@@ -0,0 +1,215 @@
+use crate::data::database::VirtualFile;
+use bincode::{Decode, Encode};
+use jam_ready::utils::file_digest::md5_digest;
+use jam_ready::utils::local_archive::LocalArchive;
+use jam_ready::utils::text_process::process_path_text;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env::current_dir;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use walkdir::WalkDir;
+
+/// Default cache size limit (MB)
+pub const DEFAULT_CACHE_LIMIT_MB: u64 = 4096;
+
+/// Local cache index
+/// Tracks the file versions cached in PATH_CACHE, so the least recently used ones can be evicted
+#[derive(Serialize, Deserialize, Encode, Decode, Clone, Debug, PartialEq)]
+pub struct LocalCache {
+    /// Cached files, by path relative to the cache directory
+    #[serde(rename = "entries")]
+    pub entries: HashMap<String, LocalCacheEntry>,
+
+    /// Size limit in MB (0 means unlimited)
+    #[serde(rename = "limit")]
+    pub limit_mb: u64,
+}
+
+#[derive(Default, Serialize, Deserialize, Encode, Decode, Clone, Debug, PartialEq)]
+pub struct LocalCacheEntry {
+    /// Virtual file path when cached (empty if unknown)
+    #[serde(rename = "file")]
+    pub file_path: String,
+
+    /// Cached version
+    #[serde(rename = "version")]
+    pub version: u32,
+
+    /// File size
+    #[serde(rename = "size")]
+    pub size: u64,
+
+    /// Digest when cached (empty if unknown)
+    #[serde(rename = "digest")]
+    pub digest: String,
+
+    /// Last use, Unix time (seconds)
+    #[serde(rename = "used")]
+    pub last_used: u64,
+
+    /// Pinned versions are never evicted
+    #[serde(rename = "pinned")]
+    pub pinned: bool,
+}
+
+impl Default for LocalCache {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            limit_mb: DEFAULT_CACHE_LIMIT_MB,
+        }
+    }
+}
+
+impl LocalArchive for LocalCache {
+    type DataType = LocalCache;
+
+    fn relative_path() -> String {
+        env!("FILE_LOCAL_CACHE").to_string()
+    }
+}
+
+impl LocalCache {
+    /// Cache directory
+    pub fn directory() -> Option<PathBuf> {
+        current_dir().ok().map(|current| current.join(env!("PATH_CACHE")))
+    }
+
+    /// Cache path of a file version
+    pub fn path_of(file: &VirtualFile, version: u32) -> Option<PathBuf> {
+        let real = file.real_path_version(version).filter(|real| !real.as_os_str().is_empty())?;
+        Some(Self::directory()?.join(real))
+    }
+
+    /// Key of a cached file
+    pub fn key_of(path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(Self::directory()?).ok()?;
+        Some(process_path_text(relative.display().to_string()))
+    }
+
+    /// Record a newly cached file version
+    pub fn record(&mut self, path: &Path, file: &VirtualFile, version: u32) {
+        let Some(key) = Self::key_of(path) else { return; };
+        let pinned = self.entries.get(&key).map(|entry| entry.pinned).unwrap_or(false);
+        self.entries.insert(key, LocalCacheEntry {
+            file_path: file.path(),
+            version,
+            size: fs::metadata(path).map(|metadata| metadata.len()).unwrap_or_default(),
+            digest: md5_digest(path.to_path_buf()).unwrap_or_default(),
+            last_used: now(),
+            pinned,
+        });
+    }
+
+    /// Mark a cached file as used
+    pub fn touch(&mut self, path: &Path) {
+        let Some(key) = Self::key_of(path) else { return; };
+        if let Some(entry) = self.entries.get_mut(&key) {
+            entry.last_used = now();
+        }
+    }
+
+    /// Pin or unpin a cached file, returns false if it is not cached
+    pub fn set_pinned(&mut self, path: &Path, pinned: bool) -> bool {
+        let Some(key) = Self::key_of(path) else { return false; };
+        match self.entries.get_mut(&key) {
+            Some(entry) => {
+                entry.pinned = pinned;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sync the index with the cache directory
+    /// Unknown files are adopted with their modification time, missing files are forgotten
+    pub fn scan(&mut self) {
+        let Some(directory) = Self::directory() else { return; };
+        self.entries.retain(|key, _| directory.join(key).is_file());
+
+        for entry in WalkDir::new(&directory).into_iter().filter_map(Result::ok) {
+            if !entry.file_type().is_file() { continue; }
+            let Some(key) = Self::key_of(entry.path()) else { continue; };
+            if self.entries.contains_key(&key) { continue; }
+
+            let metadata = entry.metadata().ok();
+            self.entries.insert(key, LocalCacheEntry {
+                size: metadata.as_ref().map(|metadata| metadata.len()).unwrap_or_default(),
+                last_used: metadata
+                    .and_then(|metadata| metadata.modified().ok())
+                    .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                    .map(|duration| duration.as_secs())
+                    .unwrap_or_default(),
+                ..Default::default()
+            });
+        }
+    }
+
+    /// Total size of all cached files
+    pub fn usage(&self) -> u64 {
+        self.entries.values().map(|entry| entry.size).sum()
+    }
+
+    /// Remove a cached file and its entry
+    pub fn remove(&mut self, key: &str) -> bool {
+        let Some(directory) = Self::directory() else { return false; };
+        let path = directory.join(key);
+        if path.exists() && fs::remove_file(&path).is_err() {
+            return false;
+        }
+        self.entries.remove(key).is_some()
+    }
+
+    /// Remove the least recently used, unpinned files until the cache fits the limit
+    /// The protected file (just downloaded) is kept
+    /// Returns the removed keys
+    pub fn evict(&mut self, protect: Option<&Path>) -> Vec<String> {
+        let mut removed = Vec::new();
+        if self.limit_mb == 0 {
+            return removed;
+        }
+
+        let limit = self.limit_mb * 1024 * 1024;
+        let protect = protect.and_then(Self::key_of);
+        let mut candidates: Vec<(String, u64)> = self.entries.iter()
+            .filter(|(key, entry)| !entry.pinned && Some(*key) != protect.as_ref())
+            .map(|(key, entry)| (key.clone(), entry.last_used))
+            .collect();
+        candidates.sort_by_key(|(_, last_used)| *last_used);
+
+        for (key, _) in candidates {
+            if self.usage() <= limit {
+                break;
+            }
+            if self.remove(&key) {
+                removed.push(key);
+            }
+        }
+        removed
+    }
+}
+
+/// Record a downloaded file, then evict old files
+pub async fn cache_downloaded(path: &Path, file: &VirtualFile, version: u32) {
+    let mut cache = LocalCache::read().await;
+    cache.scan();
+    cache.record(path, file, version);
+    cache.evict(Some(path));
+    LocalCache::update(&cache).await;
+}
+
+/// Mark a cached file as used
+pub async fn cache_used(path: &Path) {
+    let mut cache = LocalCache::read().await;
+    cache.touch(path);
+    LocalCache::update(&cache).await;
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
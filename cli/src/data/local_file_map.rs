@@ -1,11 +1,16 @@
 use crate::data::database::{Database, VirtualFile};
+use crate::data::ignore_rules::IgnoreRules;
 use bincode::{Decode, Encode};
 use jam_ready::utils::local_archive::LocalArchive;
 use jam_ready::utils::text_process::process_path_text;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env::current_dir;
-use std::path::PathBuf;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use jam_ready::utils::file_digest::md5_digest;
+use walkdir::WalkDir;
 
 /// Local file mapping
 #[derive(Default, Serialize, Deserialize, Encode, Decode, Clone, Debug, PartialEq)]
@@ -33,6 +38,10 @@ pub struct LocalFile {
     #[serde(rename = "digest")]
     pub local_digest: String,
 
+    /// File size (0 if unknown)
+    #[serde(rename = "size", default)]
+    pub local_size: u64,
+
     /// Is the file completed
     #[serde(rename = "cmpl")]
     pub completed: bool,
@@ -127,4 +136,134 @@ impl LocalFileMap {
         let path = process_path_text(path);
         self.file_uuids.get(&path)
     }
-}
\ No newline at end of file
+
+    /// Find tracked files that were moved or renamed locally, by digest and size
+    /// Returns (Uuid, Old local path, New local path)
+    pub fn detect_moved_files(&self, database: &Database) -> Vec<(String, String, String)> {
+        let mut moved = Vec::new();
+        let Ok(current_dir) = current_dir() else { return moved; };
+
+        // Tracked files missing from their local path
+        let missing: Vec<(&String, &LocalFile)> = self.file_paths.iter()
+            .filter(|(uuid, local_file)| {
+                !local_file.local_digest.is_empty() &&
+                !current_dir.join(&local_file.local_path).exists() &&
+                database.file_with_uuid(uuid.to_string())
+                    .map(|file| !file.path().is_empty())
+                    .unwrap_or(false)
+            })
+            .collect();
+        if missing.is_empty() {
+            return moved;
+        }
+
+        // Untracked local files (Path, Size)
+        let ignore_rules = IgnoreRules::load();
+        let untracked: Vec<(String, u64)> = WalkDir::new(&current_dir)
+            .into_iter()
+            .filter_entry(|entry| {
+                // Skip ignored directories
+                !entry.file_type().is_dir() || entry.path().strip_prefix(&current_dir)
+                    .map(|relative| relative.as_os_str().is_empty() || !ignore_rules.is_ignored_dir(&relative.to_string_lossy()))
+                    .unwrap_or(true)
+            })
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_file())
+            .filter_map(|entry| {
+                let path = entry.path().strip_prefix(&current_dir).ok()?.to_string_lossy().to_string();
+                let path = process_path_text(path);
+                if ignore_rules.is_ignored(&path) || self.file_uuids.contains_key(&path) {
+                    return None;
+                }
+                Some((path, entry.metadata().map(|metadata| metadata.len()).unwrap_or_default()))
+            })
+            .collect();
+
+        // Match by size first, and only hash the candidates
+        let mut digests: HashMap<String, String> = HashMap::new();
+        let mut claimed: HashSet<String> = HashSet::new();
+        for (uuid, local_file) in missing {
+            let mut matches = Vec::new();
+            for (path, size) in &untracked {
+                if local_file.local_size > 0 && local_file.local_size != *size {
+                    continue;
+                }
+                let digest = digests.entry(path.clone())
+                    .or_insert_with(|| md5_digest(current_dir.join(path)).unwrap_or_default());
+                if *digest == local_file.local_digest {
+                    matches.push(path.clone());
+                }
+            }
+
+            // Ambiguous copies are left to the member
+            if matches.len() == 1 && claimed.insert(matches[0].clone()) {
+                moved.push((uuid.clone(), local_file.local_path.clone(), matches.remove(0)));
+            }
+        }
+
+        moved
+    }
+
+    /// Bind a tracked file to a new local path
+    pub fn relocate(&mut self, uuid: &str, new_path: String) {
+        let new_path = process_path_text(new_path);
+        if let Some(local_file) = self.file_paths.get_mut(uuid) {
+            self.file_uuids.remove(&local_file.local_path);
+            local_file.local_path = new_path.clone();
+            self.file_uuids.insert(new_path, uuid.to_string());
+        }
+    }
+
+    /// Make a tracked local file read-only, unless the member may edit it
+    /// Returns true if the flag was changed
+    pub fn apply_permission(&self, database: &Database, uuid: &str, self_uuid: &str) -> io::Result<bool> {
+        let (Some(local_file), Some(file)) = (self.file_paths.get(uuid), database.file_with_uuid(uuid.to_string())) else {
+            return Ok(false);
+        };
+        let path = current_dir()?.join(&local_file.local_path);
+        if !path.is_file() {
+            return Ok(false);
+        }
+        set_read_only(&path, !is_writable_for(file, self_uuid))
+    }
+
+    /// Apply permissions to the whole working copy
+    /// Returns the local paths that were changed
+    pub fn apply_permissions(&self, database: &Database, self_uuid: &str) -> Vec<String> {
+        self.file_paths.iter()
+            .filter(|(uuid, _)| self.apply_permission(database, uuid, self_uuid).unwrap_or(false))
+            .map(|(_, local_file)| local_file.local_path.clone())
+            .collect()
+    }
+}
+
+/// Held by the member, or mergeable and unlocked
+pub fn is_writable_for(file: &VirtualFile, self_uuid: &str) -> bool {
+    match file.get_locker_owner_uuid() {
+        Some(owner) => owner.trim() == self_uuid.trim(),
+        None => file.is_mergeable(),
+    }
+}
+
+/// Set or clear the read-only flag of a file
+/// Returns true if the flag was changed
+pub fn set_read_only(path: &Path, read_only: bool) -> io::Result<bool> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    if permissions.readonly() == read_only {
+        return Ok(false);
+    }
+
+    #[cfg(unix)]
+    {
+        // Only restore the owner's write bit
+        use std::os::unix::fs::PermissionsExt;
+        let mode = permissions.mode();
+        permissions.set_mode(if read_only { mode & !0o222 } else { mode | 0o200 });
+    }
+    #[cfg(not(unix))]
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(read_only);
+
+    fs::set_permissions(path, permissions)?;
+    Ok(true)
+}
@@ -1,7 +1,11 @@
 pub mod database;
+pub mod database_journal;
 pub mod member;
 pub mod workspace;
 pub mod parameters;
 pub mod local_file_map;
 pub mod local_folder_map;
-pub mod client_result;
\ No newline at end of file
+pub mod local_cache;
+pub mod ignore_rules;
+pub mod client_result;
+pub mod server_hooks;
@@ -0,0 +1,90 @@
+use crate::data::ignore_rules::{glob_to_regex, normalize_glob};
+use jam_ready::utils::local_archive::LocalArchive;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Hooks run by the server on workspace events (edited by hand)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct ServerHooks {
+    #[serde(rename = "hooks", default)]
+    pub hooks: Vec<Hook>,
+}
+
+/// One hook
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Hook {
+    /// Name shown in the delivery log
+    #[serde(rename = "name")]
+    pub name: String,
+
+    /// Events running the hook (every event if empty)
+    #[serde(rename = "events", default)]
+    pub events: Vec<HookEvent>,
+
+    /// Virtual file patterns, e.g. "Scenes/**" (every file if empty)
+    #[serde(rename = "paths", default)]
+    pub paths: Vec<String>,
+
+    /// What to do
+    #[serde(rename = "action")]
+    pub action: HookAction,
+
+    /// Attempts after the first one fails
+    #[serde(rename = "retries", default = "default_retries")]
+    pub retries: u32,
+}
+
+/// Events a hook can run on
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HookEvent {
+    Commit,
+    Lock,
+    Unlock,
+    Add,
+    Remove,
+    Move,
+    Rollback,
+    Archive,
+    Join,
+}
+
+/// What a hook does with the event
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum HookAction {
+    /// Run a shell command, the event is written to its input as JSON
+    Command(String),
+
+    /// POST the event as JSON to an "http://" or "https://" URL
+    Post(String),
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+impl LocalArchive for ServerHooks {
+    type DataType = ServerHooks;
+
+    fn relative_path() -> String {
+        env!("FILE_SERVER_HOOKS").to_string()
+    }
+}
+
+impl Hook {
+    /// Check if the hook runs on an event about the paths
+    pub fn matches(&self, event: HookEvent, paths: &[&str]) -> bool {
+        if !self.events.is_empty() && !self.events.contains(&event) {
+            return false;
+        }
+
+        // Events without files (archive, join) ignore path patterns
+        if self.paths.is_empty() || paths.is_empty() {
+            return true;
+        }
+        self.paths.iter().any(|pattern| {
+            Regex::new(&format!("^{}$", glob_to_regex(&normalize_glob(pattern))))
+                .map(|regex| paths.iter().any(|path| regex.is_match(path)))
+                .unwrap_or(false)
+        })
+    }
+}
@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use jam_ready::utils::local_archive::LocalArchive;
+use regex::Regex;
+use crate::data::ignore_rules::{glob_to_regex, normalize_glob};
 
 /// Workspace - Working Environment
 /// The workspace exists for both server and client instances
@@ -59,6 +61,14 @@ pub struct ClientWorkspace {
     /// Debug output flag
     #[serde(rename = "debug")]
     pub debug: bool,
+
+    /// Sparse checkout, virtual file patterns to include (everything if empty)
+    #[serde(rename = "sparse_include", default)]
+    pub sparse_include: Vec<String>,
+
+    /// Sparse checkout, virtual file patterns to exclude
+    #[serde(rename = "sparse_exclude", default)]
+    pub sparse_exclude: Vec<String>,
 }
 
 /// Server workspace configuration
@@ -83,6 +93,18 @@ pub struct ServerWorkspace {
     /// Debug logging flag
     #[serde(rename = "debug")]
     pub enable_debug_logger: bool,
+
+    /// Serve the HTTP/JSON API
+    #[serde(rename = "http", default)]
+    pub enable_http_api: bool,
+
+    /// Address of the HTTP/JSON API (local only by default)
+    #[serde(rename = "http_addr", default = "default_http_address")]
+    pub http_address: String,
+}
+
+pub fn default_http_address() -> String {
+    format!("127.0.0.1:{}", env!("DEFAULT_HTTP_PORT"))
 }
 
 impl Default for Workspace {
@@ -105,6 +127,25 @@ impl LocalArchive for Workspace {
     }
 }
 
+impl ClientWorkspace {
+    /// Check if a virtual file belongs to the sparse checkout
+    pub fn in_sparse(&self, path: &str) -> bool {
+        let included = self.sparse_include.is_empty() ||
+            self.sparse_include.iter().any(|pattern| sparse_match(pattern, path));
+        included && !self.sparse_exclude.iter().any(|pattern| sparse_match(pattern, path))
+    }
+}
+
+/// Match a sparse pattern against a virtual file path ("Audio/" matches the whole directory)
+fn sparse_match(pattern: &str, path: &str) -> bool {
+    let directory = pattern.ends_with('/');
+    let pattern = normalize_glob(pattern);
+    let pattern = if directory { format!("{}/**", pattern) } else { pattern };
+    Regex::new(&format!("^{}$", glob_to_regex(&pattern)))
+        .map(|regex| regex.is_match(path))
+        .unwrap_or(false)
+}
+
 pub async fn debug_mode(debug: bool) {
     let mut workspace = Workspace::read().await;
     let Some(mut client) = workspace.client.clone() else {
@@ -1,17 +1,61 @@
-use crate::cli_commands::client::client_workspace_main;
+use crate::cli_commands::client::{client_workspace_main, ClientWorkspaceEntry};
+use crate::data::client_result::{take_json_output, JsonOutput};
 use crate::data::workspace::{debug_mode, Workspace};
 use crate::linker::linker_config::LinkerConfig;
+use crate::linker::linker_events::{event_monitor, LinkerEvent, LinkerEventKind};
+use crate::linker::linker_protocol::{read_frame, write_frame, LinkerRequest, LinkerResponse, LINKER_FEATURES, LINKER_PROTOCOL_VERSION};
+use crate::service::commands::utils_file_transmitter::{set_progress_sink, TransferProgress};
+use clap::CommandFactory;
 use jam_ready::utils::local_archive::LocalArchive;
 use jam_ready::utils::text_process::split_to_args;
+use std::collections::{HashSet, VecDeque};
 use std::env::current_dir;
+use std::future::Future;
 use std::io::{Error, ErrorKind};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::signal::ctrl_c;
+use tokio::spawn;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::broadcast::{channel, Receiver, Sender};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio::sync::{Mutex, MutexGuard};
+use tokio::task::{spawn_local, LocalSet};
 use tokio::time::sleep;
 use tokio::select;
 
+/// Events buffered for each subscriber before the oldest are dropped
+const EVENT_BUFFER_SIZE: usize = 256;
+
+/// Commands share the workspace files and the result output, so they run one at a time
+static COMMAND_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Exclusive access to the workspace while a command runs
+/// Dropping it (also when a command is cancelled) clears what the command reported
+pub struct CommandGuard {
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl CommandGuard {
+    /// Wait for the running command, then stream transfer progress to the sink (if any)
+    pub async fn acquire(progress: Option<UnboundedSender<TransferProgress>>) -> CommandGuard {
+        let lock = COMMAND_LOCK.lock().await;
+        set_progress_sink(progress);
+        CommandGuard { _lock: lock }
+    }
+}
+
+impl Drop for CommandGuard {
+    fn drop(&mut self) {
+        set_progress_sink(None);
+        let _ = take_json_output();
+    }
+}
+
 pub async fn jam_linker_entry(linker_config: LinkerConfig) {
 
     // Workspace type check
@@ -36,57 +80,282 @@ pub async fn jam_linker_entry(linker_config: LinkerConfig) {
 
     let sleep_duration = (60.0 * linker_config.sleep_minutes).clamp(10.0, 60.0 * 60.0 * 24.0);
 
+    // Connections run on this thread, commands wait for each other through the command lock
+    let local = LocalSet::new();
+    local.run_until(async move {
+        let (events, _) = channel::<LinkerEvent>(EVENT_BUFFER_SIZE);
+        spawn_local(event_monitor(events.clone(), linker_config.event_sync_seconds));
+
+        let connections = Arc::new(AtomicUsize::new(0));
+        loop {
+            select! {
+
+                // Auto shutdown
+                _ = sleep(Duration::from_secs_f64(sleep_duration)) => {
+                    if connections.load(Ordering::Relaxed) > 0 { continue; }
+                    let exit_text = "\"Zzz... Wake me up when you need me.\"";
+                    println!("{}", exit_text);
+                    break;
+                }
+
+                // Ctrl + C shutdown
+                Ok(()) = ctrl_c() => {
+                    println!("Good bye!");
+                    break;
+                }
+
+                // Process accept
+                Ok((stream, _)) = listener.accept() => {
+                    let events = events.clone();
+                    let connections = connections.clone();
+                    connections.fetch_add(1, Ordering::Relaxed);
+                    spawn_local(async move {
+                        process_input(stream, events).await;
+                        connections.fetch_sub(1, Ordering::Relaxed);
+                    });
+                }
+            }
+        }
+    }).await;
+
+    // Disable debug (linker mode)
+    debug_mode(false).await;
+}
+
+async fn process_input(stream: TcpStream, events: Sender<LinkerEvent>) {
+
+    let addr_str =
+        if let Ok(addr) = stream.peer_addr() { addr.to_string() } else { "Unknown".to_string() };
+    println!("[{}] Login.", addr_str);
+
+    // Framed clients start with the length header, its first byte is zero for every accepted frame
+    let mut first = [0u8; 1];
+    match stream.peek(&mut first).await {
+        Ok(1) if first[0] == 0 => process_framed_input(stream, events).await,
+        Ok(1) => process_text_input(stream).await,
+        _ => {}
+    }
+
+    println!("[{}] Logout.", addr_str);
+}
+
+/// Command waiting in the queue of a framed connection
+struct QueuedCommand {
+    id: u64,
+    args: Vec<String>,
+    progress: bool,
+}
+
+/// Command being executed for a framed connection
+struct RunningCommand {
+    id: u64,
+    future: Pin<Box<dyn Future<Output = JsonOutput>>>,
+}
+
+/// State of a framed connection
+#[derive(Default)]
+struct LinkerConnection {
+    queue: VecDeque<QueuedCommand>,
+    running: Option<RunningCommand>,
+    subscription: Option<Receiver<LinkerEvent>>,
+    subscribed: HashSet<LinkerEventKind>,
+}
+
+/// Framed JSON protocol (see docs/linker_protocol.md)
+async fn process_framed_input(stream: TcpStream, events: Sender<LinkerEvent>) {
+    let (mut reader, mut writer) = stream.into_split();
+
+    // Read frames in the background, so running commands can be cancelled
+    let (frame_tx, mut frame_rx) = unbounded_channel();
+    let read_task = spawn(async move {
+        while let Ok(frame) = read_frame(&mut reader).await {
+            if frame_tx.send(frame).is_err() { break; }
+        }
+    });
+
+    let (progress_tx, mut progress_rx) = unbounded_channel();
+    let mut connection = LinkerConnection::default();
+
     loop {
+        // Start the next command, it waits for commands of other connections
+        if connection.running.is_none() && let Some(command) = connection.queue.pop_front() {
+            println!("~# [{}] {}", command.id, command.args.join(" "));
+            let progress = if command.progress { Some(progress_tx.clone()) } else { None };
+            connection.running = Some(RunningCommand {
+                id: command.id,
+                future: Box::pin(run_command(command.args, progress)),
+            });
+        }
+
+        // Field borrows for the select branches
+        let has_running = connection.running.is_some();
+        let has_subscription = connection.subscription.is_some();
+        let running = connection.running.as_mut();
+        let subscription = connection.subscription.as_mut();
+
+        let mut responses = Vec::new();
         select! {
+            frame = frame_rx.recv() => {
+                let Some(frame) = frame else { break; };
+                match serde_json::from_slice::<LinkerRequest>(&frame) {
+                    Ok(request) => responses.extend(connection.process_request(request, &events)),
+                    Err(err) => responses.push(LinkerResponse::Error {
+                        id: None,
+                        message: format!("Invalid request: {}", err),
+                    }),
+                }
+            }
 
-            // Auto shutdown
-            _ = sleep(Duration::from_secs_f64(sleep_duration)) => {
-                let exit_text = "\"Zzz... Wake me up when you need me.\"";
-                println!("{}", exit_text);
-                break;
+            output = async move { running.unwrap().future.as_mut().await }, if has_running => {
+                let id = connection.running.take().unwrap().id;
+
+                // Progress reported before the command finished comes first
+                while let Ok(progress) = progress_rx.try_recv() {
+                    responses.push(LinkerResponse::Progress { id, progress });
+                }
+                responses.push(LinkerResponse::Result { id, output });
+            }
+
+            Some(progress) = progress_rx.recv() => {
+                if let Some(running) = &connection.running {
+                    responses.push(LinkerResponse::Progress { id: running.id, progress });
+                }
+            }
+
+            event = async move { subscription.unwrap().recv().await }, if has_subscription => {
+                match event {
+                    Ok(event) => if connection.subscribed.contains(&event.kind()) {
+                        responses.push(LinkerResponse::Event { event });
+                    },
+                    Err(RecvError::Lagged(count)) => responses.push(LinkerResponse::Error {
+                        id: None,
+                        message: format!("{} events were dropped, reload the workspace state", count),
+                    }),
+                    Err(RecvError::Closed) => connection.subscription = None,
+                }
             }
+        }
 
-            // Ctrl + C shutdown
-            Ok(()) = ctrl_c() => {
-                println!("Good bye!");
+        let mut closed = false;
+        for response in responses {
+            if write_frame(&mut writer, &response).await.is_err() {
+                closed = true;
                 break;
             }
+        }
+        if closed { break; }
+    }
+
+    read_task.abort();
+}
+
+impl LinkerConnection {
+
+    /// Queue commands, cancel them, manage subscriptions, or answer directly
+    fn process_request(&mut self, request: LinkerRequest, events: &Sender<LinkerEvent>) -> Option<LinkerResponse> {
+        match request {
+            LinkerRequest::Command { id, args, progress } => {
+                self.queue.push_back(QueuedCommand { id, args, progress });
+                None
+            }
 
-            // Process accept
-            Ok((stream, _)) = listener.accept() => {
+            LinkerRequest::Cancel { id, target } => {
+                // Dropping the command releases the command lock
+                if self.running.as_ref().map(|running| running.id == target).unwrap_or(false) {
+                    self.running = None;
+                    return Some(LinkerResponse::Cancelled { id: target });
+                }
+                if let Some(index) = self.queue.iter().position(|command| command.id == target) {
+                    self.queue.remove(index);
+                    return Some(LinkerResponse::Cancelled { id: target });
+                }
+                Some(LinkerResponse::Error {
+                    id: Some(id),
+                    message: format!("No running or queued command with Id {}", target),
+                })
+            }
 
-                // Only one connection is allowed.
-                // spawn(process_input(stream));
-                process_input(stream).await;
+            LinkerRequest::Capabilities { id } => {
+                let commands = ClientWorkspaceEntry::command().get_subcommands()
+                    .filter(|command| !command.is_hide_set())
+                    .map(|command| command.get_name().to_string())
+                    .collect();
+                Some(LinkerResponse::Capabilities {
+                    id,
+                    protocol: LINKER_PROTOCOL_VERSION,
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    commands,
+                    features: LINKER_FEATURES.iter().map(|feature| feature.to_string()).collect(),
+                })
+            }
+
+            LinkerRequest::Subscribe { id, events: kinds } => {
+                self.subscribed.extend(kinds);
+                if self.subscription.is_none() && !self.subscribed.is_empty() {
+                    self.subscription = Some(events.subscribe());
+                }
+                Some(self.subscriptions(id))
+            }
+
+            LinkerRequest::Unsubscribe { id, events: kinds } => {
+                // No events means all
+                if kinds.is_empty() {
+                    self.subscribed.clear();
+                } else {
+                    for kind in kinds {
+                        self.subscribed.remove(&kind);
+                    }
+                }
+
+                // Without a receiver the linker stops asking the server for changes
+                if self.subscribed.is_empty() {
+                    self.subscription = None;
+                }
+                Some(self.subscriptions(id))
             }
         }
     }
 
-    // Disable debug (linker mode)
-    debug_mode(false).await;
+    fn subscriptions(&self, id: u64) -> LinkerResponse {
+        let mut events: Vec<LinkerEventKind> = self.subscribed.iter().copied().collect();
+        events.sort_by_key(|kind| format!("{:?}", kind));
+        LinkerResponse::Subscriptions { id, events }
+    }
 }
 
-async fn process_input(mut stream: TcpStream) {
+/// Run a client command and collect its results
+async fn run_command(args: Vec<String>, progress: Option<UnboundedSender<TransferProgress>>) -> JsonOutput {
+    let _guard = CommandGuard::acquire(progress).await;
+
+    let mut command = vec!["jam".to_string(), "--output".to_string(), "json".to_string()];
+    command.extend(args);
+    if let Some(result) = client_workspace_main(command).await {
+        result.end_print();
+    }
+    take_json_output()
+}
+
+/// Plain text protocol of older clients, one command line per read
+async fn process_text_input(mut stream: TcpStream) {
 
     // Buffer
     let mut buffer :[u8; 2048] = [0; 2048];
 
-    let addr_str =
-        if let Ok(addr) = stream.peer_addr() { addr.to_string() } else { "Unknown".to_string() };
-    println!("[{}] Login.", addr_str);
-
     loop {
 
         // Read message
-        let Ok(received) = read(&mut stream, &mut buffer).await else { continue };
+        let Ok(received) = read(&mut stream, &mut buffer).await else { break };
         let received = received.trim().to_string();
         println!("~# {}", received);
 
         // Invoke command & Read result
-        let result_str = if let Some(result) = client_workspace_main(split_to_args(received)).await {
-            result.end_print()
-        } else {
-            "{}".to_string()
+        let result_str = {
+            let _guard = CommandGuard::acquire(None).await;
+            if let Some(result) = client_workspace_main(split_to_args(received)).await {
+                result.end_print()
+            } else {
+                "{}".to_string()
+            }
         };
 
         // Display result
@@ -100,8 +369,6 @@ async fn process_input(mut stream: TcpStream) {
             }
         }
     }
-
-    println!("[{}] Logout.", addr_str);
 }
 
 async fn read(stream: &mut TcpStream, buffer: &mut [u8; 2048]) -> std::io::Result<String> {
@@ -6,7 +6,11 @@ use jam_ready::utils::local_archive::LocalArchive;
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct LinkerConfig {
     pub port: u16,
-    pub sleep_minutes: f64
+    pub sleep_minutes: f64,
+
+    /// Interval of asking the server for changes while clients subscribe to events
+    #[serde(default = "default_event_sync_seconds")]
+    pub event_sync_seconds: f64
 }
 
 impl Default for LinkerConfig {
@@ -14,10 +18,15 @@ impl Default for LinkerConfig {
         Self {
             port: u16::from_str(env!("DEFAULT_LINKER_PORT")).unwrap(),
             sleep_minutes: 15.0,
+            event_sync_seconds: default_event_sync_seconds(),
         }
     }
 }
 
+fn default_event_sync_seconds() -> f64 {
+    10.0
+}
+
 impl LocalArchive for LinkerConfig {
     type DataType = LinkerConfig;
 
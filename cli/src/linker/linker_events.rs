@@ -0,0 +1,252 @@
+use crate::cli_commands::client::exec;
+use crate::data::database::Database;
+use crate::data::local_file_map::LocalFileMap;
+use crate::data::workspace::Workspace;
+use crate::linker::linker::CommandGuard;
+use crate::service::jam_client::subscribe_server_events;
+use crate::service::jam_notifier::ServerEvent;
+use jam_ready::utils::local_archive::LocalArchive;
+use jam_ready::utils::text_process::process_path_text;
+use notify::{recommended_watcher, Event, EventKind, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::env::current_dir;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::select;
+use tokio::sync::broadcast::Sender;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tokio::time::{interval, sleep};
+
+/// Quiet time before changes are reported (editors often write a file several times)
+const EVENT_DEBOUNCE_MILLIS: u64 = 500;
+
+/// Kinds of events clients can subscribe to
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LinkerEventKind {
+    /// Files of the database changed (versions, paths or locks)
+    DatabaseChanged,
+
+    /// A lock was granted or released
+    LockChanged,
+
+    /// A local file was created, modified or removed
+    FileModified,
+
+    /// A change pushed by the server (teammates adding, committing or locking files)
+    ServerChanged,
+}
+
+/// Event sent to subscribed clients
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "Kind")]
+pub enum LinkerEvent {
+    DatabaseChanged {
+        /// Changed virtual files
+        #[serde(rename = "Paths")]
+        paths: Vec<String>,
+    },
+
+    LockChanged {
+        /// Virtual file
+        #[serde(rename = "Path")]
+        path: String,
+
+        /// Uuid of the new lock owner (None if released)
+        #[serde(rename = "Owner")]
+        owner: Option<String>,
+
+        /// Held by the member of this workspace
+        #[serde(rename = "Held")]
+        held: bool,
+    },
+
+    FileModified {
+        /// Local file
+        #[serde(rename = "Path")]
+        path: String,
+
+        /// Mapped to a virtual file
+        #[serde(rename = "Tracked")]
+        tracked: bool,
+    },
+
+    ServerChanged {
+        /// Event pushed by the server
+        #[serde(rename = "Event")]
+        event: ServerEvent,
+    },
+}
+
+impl LinkerEvent {
+    pub fn kind(&self) -> LinkerEventKind {
+        match self {
+            LinkerEvent::DatabaseChanged { .. } => LinkerEventKind::DatabaseChanged,
+            LinkerEvent::LockChanged { .. } => LinkerEventKind::LockChanged,
+            LinkerEvent::FileModified { .. } => LinkerEventKind::FileModified,
+            LinkerEvent::ServerChanged { .. } => LinkerEventKind::ServerChanged,
+        }
+    }
+}
+
+/// Watch the workspace and the server, and publish events while clients are subscribed
+pub async fn event_monitor(events: Sender<LinkerEvent>, sync_seconds: f64) {
+    let Ok(current) = current_dir() else { return; };
+
+    // Forward file system events to the async loop
+    let (sender, mut receiver) = unbounded_channel::<PathBuf>();
+    let watcher = recommended_watcher(move |event: notify::Result<Event>| {
+        let Ok(event) = event else { return; };
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) { return; }
+        for path in event.paths {
+            let _ = sender.send(path);
+        }
+    });
+    let _watcher = match watcher {
+        Ok(mut watcher) => {
+            if let Err(err) = watcher.watch(&current, RecursiveMode::Recursive) {
+                eprintln!("Failed to watch the workspace: {}", err);
+            }
+            Some(watcher)
+        }
+        Err(err) => {
+            eprintln!("Failed to start the file watcher: {}", err);
+            None
+        }
+    };
+
+    let mut snapshot = Database::read().await;
+    let mut changed = HashSet::new();
+    let mut database_changed = false;
+    let mut server_sync = interval(Duration::from_secs_f64(sync_seconds.max(1.0)));
+
+    // Events pushed by the server, polled instead while not subscribed
+    let mut server_events: Option<UnboundedReceiver<ServerEvent>> = None;
+    let mut server_lost = false;
+    let mut server_changed = false;
+    let mut try_subscribe = true;
+
+    loop {
+        // Hold a server subscription while anyone listens
+        if events.receiver_count() == 0 {
+            server_events = None;
+            try_subscribe = true;
+        }
+        if server_lost {
+            server_lost = false;
+            server_events = None;
+        }
+        if server_events.is_none() && try_subscribe && events.receiver_count() > 0 {
+            try_subscribe = false;
+            let _guard = CommandGuard::acquire(None).await;
+            server_events = subscribe_server_events().await;
+        }
+        let subscribed = server_events.is_some();
+
+        select! {
+
+            // Collect changed paths, the database is checked as a whole
+            Some(path) = receiver.recv() => {
+                let Ok(relative) = path.strip_prefix(&current) else { continue; };
+                let relative = process_path_text(relative.display().to_string());
+                if relative == env!("FILE_DATABASE") {
+                    database_changed = true;
+                } else if !relative.starts_with(env!("PATH_WORKSPACE_ROOT")) {
+                    changed.insert(relative);
+                }
+            }
+
+            // Forward server changes, and fetch them once no more arrive
+            event = async { server_events.as_mut()?.recv().await }, if subscribed => match event {
+                Some(event) => {
+                    publish(&events, vec![LinkerEvent::ServerChanged { event }]);
+                    server_changed = true;
+                }
+                None => server_lost = true,
+            },
+
+            // Report them once no more events arrive
+            _ = sleep(Duration::from_millis(EVENT_DEBOUNCE_MILLIS)), if !changed.is_empty() || database_changed || server_changed => {
+                if server_changed {
+                    server_changed = false;
+                    let _guard = CommandGuard::acquire(None).await;
+                    let _ = exec(vec!["update".to_string(), "quiet".to_string()]).await;
+                }
+                if database_changed {
+                    database_changed = false;
+                    let database = Database::read().await;
+                    publish(&events, database_events(&snapshot, &database).await);
+                    snapshot = database;
+                }
+                if !changed.is_empty() {
+                    let local = LocalFileMap::read().await;
+                    let mut paths: Vec<String> = changed.drain().collect();
+                    paths.sort();
+                    publish(&events, paths.into_iter()
+                        .map(|path| LinkerEvent::FileModified {
+                            tracked: local.file_uuids.contains_key(&path),
+                            path,
+                        })
+                        .collect());
+                }
+            }
+
+            // Ask the server for changes while someone listens and the server does not push them,
+            // the database file event follows
+            _ = server_sync.tick(), if !subscribed => {
+                if events.receiver_count() > 0 {
+                    let _guard = CommandGuard::acquire(None).await;
+                    let _ = exec(vec!["update".to_string(), "quiet".to_string()]).await;
+                    try_subscribe = true;
+                }
+            }
+        }
+    }
+}
+
+/// Send events if anyone is subscribed
+fn publish(events: &Sender<LinkerEvent>, list: Vec<LinkerEvent>) {
+    if events.receiver_count() == 0 { return; }
+    for event in list {
+        let _ = events.send(event);
+    }
+}
+
+/// Compare the database with the snapshot
+async fn database_events(snapshot: &Database, database: &Database) -> Vec<LinkerEvent> {
+    let changed = database.changed_files(snapshot);
+    if changed.is_empty() {
+        return Vec::new();
+    }
+    let self_uuid = Workspace::read().await.client.map(|client| client.uuid).unwrap_or_default();
+
+    let mut paths = Vec::new();
+    let mut lock_events = Vec::new();
+    for (uuid, file) in changed {
+        let old = snapshot.file_with_uuid(uuid);
+
+        // Removed files keep their Uuid without a path
+        let path = if file.path().is_empty() {
+            old.map(|old| old.path()).unwrap_or_default()
+        } else {
+            file.path()
+        };
+        if path.is_empty() { continue; }
+
+        let old_owner = old.and_then(|old| old.get_locker_owner_uuid()).map(|owner| owner.trim().to_string());
+        let owner = file.get_locker_owner_uuid().map(|owner| owner.trim().to_string());
+        if old_owner != owner {
+            lock_events.push(LinkerEvent::LockChanged {
+                path: path.clone(),
+                held: owner.as_deref() == Some(self_uuid.trim()),
+                owner,
+            });
+        }
+        paths.push(path);
+    }
+
+    paths.sort();
+    let mut events = vec![LinkerEvent::DatabaseChanged { paths }];
+    events.extend(lock_events);
+    events
+}
@@ -0,0 +1,157 @@
+use crate::data::client_result::JsonOutput;
+use crate::linker::linker_events::{LinkerEvent, LinkerEventKind};
+use crate::service::commands::utils_file_transmitter::TransferProgress;
+use serde::{Deserialize, Serialize};
+use std::io::{Error, ErrorKind};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Version of the framed protocol, raised when messages change
+pub const LINKER_PROTOCOL_VERSION: u32 = 1;
+
+/// Largest frame accepted from linker clients (16MB)
+const MAX_FRAME_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Features announced by the capabilities request
+pub const LINKER_FEATURES: [&str; 4] = ["progress", "cancel", "queue", "events"];
+
+/// Request sent by linker clients
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "Type")]
+pub enum LinkerRequest {
+    /// Run a client command (arguments without "jam"), streaming transfer progress if "progress"
+    Command {
+        #[serde(rename = "Id")]
+        id: u64,
+
+        #[serde(rename = "Args")]
+        args: Vec<String>,
+
+        #[serde(rename = "Progress", default)]
+        progress: bool,
+    },
+
+    /// Cancel a running or queued command
+    Cancel {
+        #[serde(rename = "Id")]
+        id: u64,
+
+        #[serde(rename = "Target")]
+        target: u64,
+    },
+
+    /// Ask for protocol version, commands and features
+    Capabilities {
+        #[serde(rename = "Id")]
+        id: u64,
+    },
+
+    /// Receive events of the given kinds
+    Subscribe {
+        #[serde(rename = "Id")]
+        id: u64,
+
+        #[serde(rename = "Events")]
+        events: Vec<LinkerEventKind>,
+    },
+
+    /// Stop receiving events of the given kinds (all if empty)
+    Unsubscribe {
+        #[serde(rename = "Id")]
+        id: u64,
+
+        #[serde(rename = "Events", default)]
+        events: Vec<LinkerEventKind>,
+    },
+}
+
+/// Response sent by the linker
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "Type")]
+pub enum LinkerResponse {
+    /// Command finished (same document as "--output json")
+    Result {
+        #[serde(rename = "Id")]
+        id: u64,
+
+        #[serde(rename = "Output")]
+        output: JsonOutput,
+    },
+
+    /// Transfer progress of a running command
+    Progress {
+        #[serde(rename = "Id")]
+        id: u64,
+
+        #[serde(flatten)]
+        progress: TransferProgress,
+    },
+
+    /// Command cancelled before it finished
+    Cancelled {
+        #[serde(rename = "Id")]
+        id: u64,
+    },
+
+    /// Answer to the capabilities request
+    Capabilities {
+        #[serde(rename = "Id")]
+        id: u64,
+
+        #[serde(rename = "Protocol")]
+        protocol: u32,
+
+        #[serde(rename = "Version")]
+        version: String,
+
+        #[serde(rename = "Commands")]
+        commands: Vec<String>,
+
+        #[serde(rename = "Features")]
+        features: Vec<String>,
+    },
+
+    /// Subscribed event kinds, answer to Subscribe and Unsubscribe
+    Subscriptions {
+        #[serde(rename = "Id")]
+        id: u64,
+
+        #[serde(rename = "Events")]
+        events: Vec<LinkerEventKind>,
+    },
+
+    /// Event of a subscribed kind
+    Event {
+        #[serde(rename = "Event")]
+        event: LinkerEvent,
+    },
+
+    /// Request failed (no Id if the request could not be read)
+    Error {
+        #[serde(rename = "Id")]
+        id: Option<u64>,
+
+        #[serde(rename = "Message")]
+        message: String,
+    },
+}
+
+/// Read one frame (8 bytes big-endian length, then UTF-8 JSON)
+pub async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> std::io::Result<Vec<u8>> {
+    let size = reader.read_u64().await?;
+    if size > MAX_FRAME_SIZE {
+        return Err(Error::new(ErrorKind::InvalidData, format!("Frame too large: {} bytes", size)));
+    }
+
+    let mut frame = vec![0u8; size as usize];
+    reader.read_exact(&mut frame).await?;
+    Ok(frame)
+}
+
+/// Write one frame
+pub async fn write_frame<W: AsyncWrite + Unpin>(writer: &mut W, response: &LinkerResponse) -> std::io::Result<()> {
+    let json = serde_json::to_vec(response)
+        .map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+    writer.write_all(&(json.len() as u64).to_be_bytes()).await?;
+    writer.write_all(&json).await?;
+    writer.flush().await
+}
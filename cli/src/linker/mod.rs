@@ -1,2 +1,4 @@
 pub mod linker_config;
-pub mod linker;
\ No newline at end of file
+pub mod linker;
+pub mod linker_protocol;
+pub mod linker_events;
\ No newline at end of file
@@ -1,8 +1,10 @@
 use crate::data::database::Database;
+use crate::data::database_journal::DatabaseJournal;
 use crate::data::member::Member;
 use crate::data::member::MemberDuty::Leader;
 use crate::service::commands::utils_duty_verifier::{verify, verify_duty};
 use crate::service::jam_command::Command;
+use crate::service::jam_notifier::publish_write;
 use async_trait::async_trait;
 use jam_ready::utils::local_archive::LocalArchive;
 use jam_ready::entry_mutex_async;
@@ -56,14 +58,16 @@ impl Command for ArchiveCommand {
         // Create archive backup
         if let Some(path_str) = archive_path.to_str() {
             entry_mutex_async!(database, |guard| {
+                let snapshot = guard.clone();
                 Database::update_to(guard, path_str.to_string()).await;
                 guard.clean_histories();
+                publish_write(&snapshot, guard, &member.member_name).await;
             });
         }
 
-        // Update main database
+        // Update main database (pending journal records are folded in)
         entry_mutex_async!(database, |guard| {
-            Database::update(guard).await;
+            DatabaseJournal::fold(guard).await;
         });
     }
 }
\ No newline at end of file
@@ -1,10 +1,16 @@
+use std::collections::HashMap;
+use std::env::current_dir;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::Arc;
-use std::time::Duration;
-use crate::data::database::Database;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::data::database::{Changeset, Database, VirtualFile};
+use crate::data::database_journal::DatabaseJournal;
 use crate::data::member::Member;
 use crate::data::workspace::Workspace;
 use crate::service::commands::utils_database_sync::{sync_local, sync_remote};
 use crate::service::jam_command::Command;
+use crate::service::jam_notifier::publish_write;
 use async_trait::async_trait;
 use colored::Colorize;
 use log::{info};
@@ -18,9 +24,10 @@ use jam_ready::entry_mutex_async;
 use jam_ready::utils::file_digest::md5_digest;
 use jam_ready::utils::text_process::process_path_text;
 use crate::data::client_result::ClientResult;
-use crate::data::local_file_map::{LocalFile, LocalFileMap};
+use crate::data::local_file_map::{set_read_only, LocalFile, LocalFileMap};
+use crate::service::commands::utils_file_merger::three_way_merge;
 use crate::service::commands::utils_file_transmitter::{read_file, send_file};
-use crate::service::messages::ClientMessage::{Done, Text, Unknown};
+use crate::service::messages::ClientMessage::{Cancel, Done, Text, Unknown};
 use crate::service::messages::{ClientMessage, ServerMessage};
 use crate::service::messages::ServerMessage::{Deny, Pass};
 use crate::service::service_utils::{read_msg, send_msg};
@@ -29,7 +36,7 @@ pub struct CommitCommand;
 
 #[async_trait]
 impl Command for CommitCommand {
-    async fn local(&self, stream: &mut TcpStream, _args: Vec<&str>) -> Option<ClientResult> {
+    async fn local(&self, stream: &mut TcpStream, args: Vec<&str>) -> Option<ClientResult> {
 
         let mut command_result = ClientResult::result().await;
 
@@ -42,10 +49,19 @@ impl Command for CommitCommand {
         let mut all_count = 0;
         let mut success_count = 0;
 
+        // A file failed, so the changeset is abandoned
+        let mut cancelled = false;
+
         // File lists
         let mut success_files = Vec::new();
         let mut failed_files = Vec::new();
 
+        // Files uploaded to the server staging area (Uuid, Path, New version, Digest, Record path)
+        let mut staged = Vec::new();
+
+        // Only commit these paths if specified <message> <path|path...>
+        let only: Option<Vec<&str>> = args.get(2).map(|paths| paths.split("|").collect());
+
         // Load workspace
         let workspace = Workspace::read().await;
         if let Some(client) = workspace.client {
@@ -53,12 +69,18 @@ impl Command for CommitCommand {
             // Find files locked by current member in database
             for file in database.files() {
 
+                if let Some(only) = &only
+                    && !only.contains(&file.path().as_str()) {
+                    continue;
+                }
+
                 // Check if file is locked by current member
                 let is_locked_by_me = file.get_locker_owner_uuid()
                     .map(|owner_uuid| owner_uuid.trim() == client.uuid.trim())
                     .unwrap_or(false);
 
-                if !is_locked_by_me {
+                // Unlocked mergeable files are only committed when modified locally
+                if !is_locked_by_me && (!file.is_mergeable_unlocked() || !is_modified_locally(&database, &local, file)) {
                     continue;
                 }
 
@@ -111,6 +133,10 @@ impl Command for CommitCommand {
                     (Some(local_file), _) if local_file.local_version == file.version()
                         && local_file.local_digest != current_digest => true,
 
+                    // Mergeable files may be based on an older version, the server merges them
+                    (Some(local_file), _) if file.is_mergeable()
+                        && local_file.local_digest != current_digest => true,
+
                     // Other cases don't allow commit
                     _ => false,
                 };
@@ -125,74 +151,175 @@ impl Command for CommitCommand {
                 all_count += 1;
                 let record_file_path = process_path_text(client_path.display().to_string());
 
+                // Version the local file is based on
+                let base_version = local_file.map(|local_file| local_file.local_version).unwrap_or(file.version());
+
+                // Empty files cannot be uploaded
+                if std::fs::metadata(&client_path).map(|metadata| metadata.len() == 0).unwrap_or(true) {
+                    command_result.err(format!("Cannot upload empty file {}", file.path()).as_str());
+                    failed_files.push(record_file_path);
+                    cancelled = true;
+                    break;
+                }
+
                 // Request upload permission from server
-                send_msg(stream, &Text(format!("{}|{}", file.path(), completed_commit))).await;
-                match read_msg::<ServerMessage>(stream).await {
+                send_msg(stream, &Text(format!("{}|{}|{}", file.path(), base_version, completed_commit))).await;
+                let uploaded = match read_msg::<ServerMessage>(stream).await {
                     Pass => {
 
-                        // Upload file
-                        if send_file(stream, client_path.clone()).await.is_ok() {
+                        // Upload file to staging, and wait until the server has staged it
+                        send_file(stream, client_path.clone()).await.is_ok()
+                            && read_msg::<ServerMessage>(stream).await == ServerMessage::Done
+                    }
+                    Deny(reason) => {
+                        command_result.err(format!("{}: {}", file.path(), reason).as_str());
+                        false
+                    }
+                    _ => false
+                };
+
+                // One failed file cancels the whole changeset
+                match database.uuid_of_path(file.path()) {
+                    Some(uuid) if uploaded => staged.push((uuid, file.path(), base_version, file.version() + 1, current_digest, record_file_path)),
+                    _ => {
+                        failed_files.push(record_file_path);
+                        cancelled = true;
+                        break;
+                    }
+                }
+            }
+
+            // Nothing is applied, the server discards the staged files
+            if cancelled {
+                send_msg(stream, &Cancel).await;
+                for (_, _, _, _, _, record_file_path) in staged {
+                    failed_files.push(record_file_path);
+                }
+            } else {
+
+                // Apply all staged files as one changeset
+                send_msg(stream, &Done).await;
+                match read_msg::<ServerMessage>(stream).await {
+                    ServerMessage::Text(response) => {
+
+                        // Changeset Id, followed by the paths of merged files
+                        let mut response = response.split("|");
+                        let changeset_id = response.next().unwrap_or_default().to_string();
+                        let merged: Vec<&str> = response.collect();
+
+                        for (uuid, path, base_version, new_version, digest, record_file_path) in staged {
                             success_count += 1;
-                            success_files.push(record_file_path.clone());
+                            let size = std::fs::metadata(&record_file_path).map(|metadata| metadata.len()).unwrap_or_default();
+
+                            // Short locks are released by the commit, so the file is protected again
+                            let released = database.file_with_uuid(uuid.clone())
+                                .map(|file| !file.is_longer_lock_unchecked() && !file.is_mergeable())
+                                .unwrap_or(false);
+                            if released {
+                                let _ = set_read_only(Path::new(&record_file_path), true);
+                            }
+                            success_files.push(record_file_path);
+
+                            // Merged files stay at their base version, so the merged result is downloaded on the next update
+                            let new_version = if merged.contains(&path.as_str()) {
+                                command_result.log(format!("Merged {}", path.cyan()).as_str());
+                                base_version
+                            } else {
+                                new_version
+                            };
 
                             // Update local mapping
-                            if let Some(uuid) = database.uuid_of_path(file.path()) {
-                                let new_version = file.version() + 1;
-                                if let Some(local_file) = local.file_paths.get_mut(&uuid) {
-                                    local_file.local_version = new_version;
-                                    local_file.local_digest = current_digest.clone();
-                                    local_file.completed = false;
-                                    local_file.completed_digest = String::new();
-                                    local_file.completed_commit = String::new();
-                                } else {
-                                    local.file_paths.insert(uuid.clone(), LocalFile {
-                                        local_path: file.path().to_string(),
-                                        local_version: new_version,
-                                        local_digest: current_digest,
-                                        completed: false,
-                                        completed_digest: String::new(),
-                                        completed_commit: String::new(),
-                                    });
-                                    local.file_uuids.insert(file.path().to_string(), uuid);
-                                }
-                                LocalFileMap::update(&local).await;
+                            if let Some(local_file) = local.file_paths.get_mut(&uuid) {
+                                local_file.local_version = new_version;
+                                local_file.local_digest = digest;
+                                local_file.local_size = size;
+                                local_file.completed = false;
+                                local_file.completed_digest = String::new();
+                                local_file.completed_commit = String::new();
+                            } else {
+                                local.file_paths.insert(uuid.clone(), LocalFile {
+                                    local_path: path.clone(),
+                                    local_version: new_version,
+                                    local_digest: digest,
+                                    local_size: size,
+                                    completed: false,
+                                    completed_digest: String::new(),
+                                    completed_commit: String::new(),
+                                });
+                                local.file_uuids.insert(path, uuid);
                             }
-                        } else {
+                        }
+                        LocalFileMap::update(&local).await;
+
+                        command_result.log(format!("Changeset {}", changeset_id.cyan()).as_str());
+                        command_result.metadata("changeset".to_string(), changeset_id);
+                    }
+                    ServerMessage::Conflict(conflicts) => {
+                        command_result.err("Merge conflict, nothing committed.");
+
+                        for (path, base_version, latest_version, latest_real) in conflicts {
+
+                            // Receive the latest version into the local cache, so it can be compared with "diff"
+                            let received = match current_dir() {
+                                Ok(current) if !latest_real.is_empty() =>
+                                    read_file(stream, current.join(env!("PATH_CACHE")).join(&latest_real)).await.is_ok(),
+                                _ => false
+                            };
+
+                            command_result.err(format!("Conflict: {} (based on v{}, latest v{})", path, base_version, latest_version).as_str());
+                            if received {
+                                command_result.log(format!("Compare with: jam diff {}", path).as_str());
+                            }
+
+                            // The local file is now resolved against the latest version, and must be completed again
+                            if let Some(uuid) = database.uuid_of_path(path.clone())
+                                && let Some(local_file) = local.file_paths.get_mut(&uuid) {
+                                local_file.local_version = latest_version;
+                                local_file.completed = false;
+                                local_file.completed_digest = String::new();
+                            }
+                        }
+                        LocalFileMap::update(&local).await;
+
+                        for (_, _, _, _, _, record_file_path) in staged {
                             failed_files.push(record_file_path);
                         }
                     }
-                    _ => {
-                        failed_files.push(record_file_path);
+                    Deny(reason) => {
+                        command_result.err(reason.as_str());
+                        for (_, _, _, _, _, record_file_path) in staged {
+                            failed_files.push(record_file_path);
+                        }
                     }
+                    _ => {}
                 }
             }
 
-            // Print commit results
+            // Print commit results, the changeset is applied as a whole or not at all
             if all_count == 0 {
                 command_result.err("No File Committed.");
             } else if success_count == all_count {
-                command_result.log(format!("Committed {} file(s).", all_count).as_str());
+                command_result.log(format!("Committed {} file(s).", success_count).as_str());
             } else {
-                command_result.warn(format!("Committed {} file(s).", all_count).as_str());
+                command_result.err(format!("Changeset failed, committed 0 of {} file(s).", all_count).as_str());
             }
+            command_result.metadata("committed".to_string(), success_count.to_string());
 
             // Print success and failed file lists
             if !success_files.is_empty() {
                 command_result.log("Success file(s):");
                 for file in success_files {
-                    command_result.log(format!("{}", file).as_str());
+                    command_result.log(file.as_str());
                 }
             }
 
             if !failed_files.is_empty() {
                 command_result.log("Failed file(s):");
                 for file in failed_files {
-                    command_result.log(format!("{}", file).as_str());
+                    command_result.log(file.as_str());
                 }
             }
 
-            // Send completion message
-            send_msg(stream, &Done).await;
             return Some(command_result)
         }
         None
@@ -201,17 +328,18 @@ impl Command for CommitCommand {
     async fn remote(
         &self,
         stream: &mut TcpStream,
-        _args: Vec<&str>,
-        (uuid, _member): (String, &Member),
+        args: Vec<&str>,
+        (uuid, member): (String, &Member),
         database: Arc<Mutex<Database>>
     ) {
-        let mut changed = false;
-
         // Sync database
         entry_mutex_async!(database, |guard| {
             sync_remote(stream, guard).await;
         });
 
+        // Files received into staging
+        let mut staged: Vec<StagedFile> = Vec::new();
+
         loop {
             select! {
                 // 60 seconds timeout
@@ -219,15 +347,30 @@ impl Command for CommitCommand {
 
                 // Process messages
                 msg = read_msg::<ClientMessage>(stream) => {
-                    if msg == Unknown || msg == Done {
+                    if msg == Unknown {
+                        break;
+                    }
+
+                    // A file failed on the client, nothing is applied
+                    if msg == Cancel {
+                        info!("Changeset cancelled by {}", member.member_name);
                         break;
                     }
 
+                    // All files uploaded, apply the changeset
+                    if msg == Done {
+                        let message = args.get(1).map(|message| message.to_string());
+                        apply_changeset(stream, &database, (&uuid, member), message, staged).await;
+                        return;
+                    }
+
                     if let Text(msg) = msg {
 
-                        let split = msg.split("|").into_iter().collect::<Vec<&str>>();
+                        // <path>|<base version>|<description>
+                        let split = msg.splitn(3, "|").collect::<Vec<&str>>();
                         let path = split[0];
-                        let commit_description = split[1];
+                        let base_version = split.get(1).and_then(|version| u32::from_str(version).ok());
+                        let commit_description = split.get(2).unwrap_or(&"Update");
 
                         let pack;
 
@@ -243,7 +386,7 @@ impl Command for CommitCommand {
                                 .map(|owner| owner.trim() == uuid.trim())
                                 .unwrap_or(false);
 
-                            if !is_locked_by_client {
+                            if !is_locked_by_client && !file.is_mergeable_unlocked() {
                                 send_msg(stream, &Deny("Lock mismatch".to_string())).await;
                                 continue;
                             }
@@ -251,8 +394,8 @@ impl Command for CommitCommand {
                             // Generate new file UUID
                             let real_file_uuid = Uuid::new_v4().to_string();
 
-                            // Get server path
-                            if let Some(path) = file.server_path_temp(real_file_uuid.clone()) {
+                            // Get staging path
+                            if let Some(path) = staging_path(&real_file_uuid) {
                                 send_msg(stream, &Pass).await;
                                 pack = Some((path.clone(), real_file_uuid.clone()));
                             } else {
@@ -261,27 +404,17 @@ impl Command for CommitCommand {
                             }
                         });
 
-                        if let Some((real_path, real_file_uuid)) = pack {
-
-                            // Receive file
-                            if read_file(stream, real_path.clone()).await.is_ok() {
+                        if let Some((staging_path, real_file_uuid)) = pack {
 
-                                entry_mutex_async!(database, |guard| {
-                                    let Some(file) = guard.file_mut(path.to_string()) else {
-                                        continue;
-                                    };
-
-                                    // Update file
-                                    file.update(real_file_uuid, commit_description.to_string());
-                                    info!("Update file {}: \"{}\"", file.path(), commit_description);
-
-                                    // Release lock if not long-term
-                                    if !file.is_longer_lock_unchecked() {
-                                        file.throw_locker();
-                                    }
+                            // Receive file into staging
+                            if read_file(stream, staging_path.clone()).await.is_ok() {
+                                staged.push(StagedFile {
+                                    path: path.to_string(),
+                                    description: commit_description.to_string(),
+                                    real_file_uuid,
+                                    base_version,
                                 });
-
-                                changed = true;
+                                send_msg(stream, &ServerMessage::Done).await;
                                 continue;
                             }
                         }
@@ -293,10 +426,284 @@ impl Command for CommitCommand {
             }
         }
 
-        if changed {
-            entry_mutex_async!(database, |guard| {
-                Database::update(guard).await;
-            });
+        // Connection lost or timed out, nothing is applied
+        discard_staged(&staged).await;
+    }
+}
+
+/// A file received into staging
+struct StagedFile {
+    /// Virtual file path
+    path: String,
+
+    /// Commit description
+    description: String,
+
+    /// Uuid of the uploaded real file
+    real_file_uuid: String,
+
+    /// Version the upload is based on
+    base_version: Option<u32>,
+}
+
+/// Apply staged files to the database as one changeset
+async fn apply_changeset(
+    stream: &mut TcpStream,
+    database: &Arc<Mutex<Database>>,
+    (uuid, member): (&String, &Member),
+    message: Option<String>,
+    staged: Vec<StagedFile>
+) {
+    if staged.is_empty() {
+        send_msg(stream, &ServerMessage::Done).await;
+        return;
+    }
+
+    entry_mutex_async!(database, |guard| {
+
+        // Check all locks again before changing anything
+        for staged_file in &staged {
+            let Some(file) = guard.file(staged_file.path.clone()) else {
+                send_msg(stream, &Deny(format!("Virtual file not found: {}", staged_file.path))).await;
+                discard_staged(&staged).await;
+                return;
+            };
+
+            let is_locked_by_client = file.get_locker_owner_uuid()
+                .map(|owner| owner.trim() == uuid.trim())
+                .unwrap_or(false);
+
+            if !is_locked_by_client && !file.is_mergeable_unlocked() {
+                send_msg(stream, &Deny(format!("Lock mismatch: {}", staged_file.path))).await;
+                discard_staged(&staged).await;
+                return;
+            }
+        }
+
+        // Merge files based on an older version
+        let mut merged = Vec::new();
+        let mut conflicts = Vec::new();
+        for staged_file in &staged {
+            let Some(file) = guard.file(staged_file.path.clone()) else { continue; };
+            let Some(base_version) = staged_file.base_version else { continue; };
+            if !file.is_mergeable() || base_version == file.version() {
+                continue;
+            }
+
+            if merge_staged(file, base_version, &staged_file.real_file_uuid).await {
+                merged.push(staged_file.path.clone());
+            } else {
+                // The latest real file is only sent if it exists
+                let latest = file.server_path().filter(|path| path.is_file());
+                let latest_real = if latest.is_some() { file.real_path() } else { String::new() };
+                conflicts.push(((staged_file.path.clone(), base_version, file.version(), latest_real), latest));
+            }
+        }
+
+        // Any conflict rejects the whole changeset, the latest versions are sent for manual resolution
+        if !conflicts.is_empty() {
+            info!("Changeset rejected, {} merge conflict(s)", conflicts.len());
+            let (conflicts, latest_paths): (Vec<_>, Vec<_>) = conflicts.into_iter().unzip();
+            send_msg(stream, &ServerMessage::Conflict(conflicts)).await;
+            for latest_path in latest_paths.into_iter().flatten() {
+                let _ = send_file(stream, latest_path).await;
+            }
+            discard_staged(&staged).await;
+            return;
+        }
+
+        // Move staged files into storage
+        let stored = match current_dir() {
+            Ok(root) => store_staged(&root, &staged).await,
+            Err(err) => Err(err.to_string()),
+        };
+        if let Err(err) = stored {
+            send_msg(stream, &Deny(err)).await;
+            discard_staged(&staged).await;
+            return;
+        }
+
+        // Update files
+        let snapshot = guard.clone();
+        let mut files = HashMap::new();
+        for StagedFile { path, description: commit_description, real_file_uuid, .. } in &staged {
+            let Some(file_uuid) = guard.uuid_of_path(path.clone()) else { continue; };
+            let Some(file) = guard.file_mut(path.clone()) else { continue; };
+
+            let before = file.version();
+            file.update(real_file_uuid.clone(), commit_description.clone());
+
+            // Digest lets clients fetch this version from peers
+            if let Some(Ok(digest)) = storage_path(real_file_uuid).map(md5_digest) {
+                file.set_version_digest(file.version(), digest);
+            }
+            files.insert(file_uuid, (before, file.version()));
+            info!("Update file {}: \"{}\"", file.path(), commit_description);
+
+            // Release lock if not long-term
+            if !file.is_longer_lock_unchecked() {
+                file.throw_locker();
+            }
+        }
+
+        // Record changeset
+        let message = message.unwrap_or_else(|| {
+            let mut descriptions: Vec<String> = staged.iter().map(|staged_file| staged_file.description.clone()).collect();
+            descriptions.sort();
+            descriptions.dedup();
+            descriptions.join("; ")
+        });
+        let changeset_id = guard.insert_changeset(Changeset {
+            message: message.clone(),
+            member: member.member_name.clone(),
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            files,
+            reverted: false,
+        });
+        info!("Changeset {} ({} files): \"{}\"", changeset_id, staged.len(), message);
+
+        // Write to journal before confirming
+        DatabaseJournal::write(&snapshot, guard, uuid).await;
+        publish_write(&snapshot, guard, &member.member_name).await;
+        if merged.is_empty() {
+            send_msg(stream, &ServerMessage::Text(changeset_id)).await;
+        } else {
+            send_msg(stream, &ServerMessage::Text(format!("{}|{}", changeset_id, merged.join("|")))).await;
         }
+    });
+}
+
+/// Merge a staged file with the latest version, the merged text replaces the staged file
+async fn merge_staged(file: &VirtualFile, base_version: u32, real_file_uuid: &str) -> bool {
+    let Some(staged_path) = staging_path(real_file_uuid) else { return false; };
+
+    // Version 0 has no content
+    let base = match file.server_path_version(base_version) {
+        Some(path) if base_version > 0 => tokio::fs::read(path).await.ok(),
+        _ => Some(Vec::new()),
+    };
+    let theirs = match file.server_path() {
+        Some(path) => tokio::fs::read(path).await.ok(),
+        None => None,
+    };
+    let ours = tokio::fs::read(&staged_path).await.ok();
+
+    // Only text files can be merged
+    let (Some(base), Some(theirs), Some(ours)) = (base, theirs, ours) else { return false; };
+    let (Ok(base), Ok(theirs), Ok(ours)) = (String::from_utf8(base), String::from_utf8(theirs), String::from_utf8(ours)) else {
+        return false;
+    };
+
+    match three_way_merge(&base, &ours, &theirs) {
+        Some(merged) => tokio::fs::write(staged_path, merged).await.is_ok(),
+        None => false
+    }
+}
+
+/// Move staged files into storage under the server root, stored files are removed again if one fails
+async fn store_staged(root: &Path, staged: &[StagedFile]) -> Result<(), String> {
+    let mut moved = Vec::new();
+    for StagedFile { path, real_file_uuid, .. } in staged {
+        let from = root.join(env!("PATH_STAGING")).join(real_file_uuid);
+        let to = root.join(env!("PATH_DATABASE")).join(real_file_uuid);
+        if let Err(err) = move_file(&from, &to).await {
+            for to in moved {
+                let _ = tokio::fs::remove_file(to).await;
+            }
+            return Err(format!("Cannot store {}: {}", path, err));
+        }
+        moved.push(to);
+    }
+    Ok(())
+}
+
+/// Move a file, creating the target directory if needed
+async fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if let Some(parent) = to.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::rename(from, to).await
+}
+
+/// Check if a local file differs from its last synced content
+fn is_modified_locally(database: &Database, local: &LocalFileMap, file: &VirtualFile) -> bool {
+    let Some(uuid) = database.uuid_of_path(file.path()) else { return false; };
+    let Some(local_file) = local.file_paths.get(&uuid) else { return false; };
+    match local.file_to_path(database, file) {
+        Some(path) if path.exists() => md5_digest(path)
+            .map(|digest| digest != local_file.local_digest)
+            .unwrap_or(false),
+        _ => false
+    }
+}
+
+/// Remove staged files that were not applied
+async fn discard_staged(staged: &Vec<StagedFile>) {
+    for StagedFile { real_file_uuid, .. } in staged {
+        if let Some(path) = staging_path(real_file_uuid) {
+            let _ = tokio::fs::remove_file(path).await;
+        }
+    }
+}
+
+/// Get staging path of an uploading file
+fn staging_path(real_file_uuid: &str) -> Option<PathBuf> {
+    current_dir().ok().map(|current| current.join(env!("PATH_STAGING")).join(real_file_uuid))
+}
+
+/// Get storage path of a committed file
+fn storage_path(real_file_uuid: &str) -> Option<PathBuf> {
+    current_dir().ok().map(|current| current.join(env!("PATH_DATABASE")).join(real_file_uuid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn staged_file(real_file_uuid: &str) -> StagedFile {
+        StagedFile {
+            path: "Proj/cfg.json".to_string(),
+            description: "Update".to_string(),
+            real_file_uuid: real_file_uuid.to_string(),
+            base_version: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn stores_into_empty_server_directory() {
+        let root = std::env::temp_dir().join(format!("jam_commit_{}", Uuid::new_v4()));
+        let real_file_uuid = Uuid::new_v4().to_string();
+        let staged = root.join(env!("PATH_STAGING")).join(&real_file_uuid);
+        std::fs::create_dir_all(staged.parent().unwrap()).unwrap();
+        std::fs::write(&staged, "{}").unwrap();
+
+        let stored = store_staged(&root, &[staged_file(&real_file_uuid)]).await;
+        let storage = root.join(env!("PATH_DATABASE")).join(&real_file_uuid);
+        let content = std::fs::read_to_string(&storage);
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert_eq!(stored, Ok(()));
+        assert_eq!(content.unwrap(), "{}");
+        assert!(!staged.exists());
+    }
+
+    #[tokio::test]
+    async fn removes_stored_files_when_one_fails() {
+        let root = std::env::temp_dir().join(format!("jam_commit_{}", Uuid::new_v4()));
+        let first = Uuid::new_v4().to_string();
+        let missing = Uuid::new_v4().to_string();
+        let staged = root.join(env!("PATH_STAGING")).join(&first);
+        std::fs::create_dir_all(staged.parent().unwrap()).unwrap();
+        std::fs::write(&staged, "{}").unwrap();
+
+        let stored = store_staged(&root, &[staged_file(&first), staged_file(&missing)]).await;
+        let first_stored = root.join(env!("PATH_DATABASE")).join(&first).exists();
+        let _ = std::fs::remove_dir_all(&root);
+
+        assert!(stored.is_err());
+        assert!(!first_stored);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,339 @@
+use crate::data::client_result::{ClientResult, ClientResultQueryProcess};
+use crate::data::database::{Database, VirtualFile};
+use crate::data::local_cache::{cache_downloaded, cache_used};
+use crate::data::local_file_map::LocalFileMap;
+use crate::data::member::Member;
+use crate::service::commands::file_viewer::local_cache_file;
+use crate::service::commands::utils_database_sync::{sync_local, sync_remote};
+use crate::service::commands::utils_file_transmitter::{read_file, send_file};
+use crate::service::jam_command::Command;
+use crate::service::messages::{ClientMessage, ServerMessage};
+use crate::service::service_utils::{read_msg, send_msg};
+use async_trait::async_trait;
+use colored::Colorize;
+use jam_ready::entry_mutex_async;
+use jam_ready::utils::file_digest::md5_digest;
+use jam_ready::utils::local_archive::LocalArchive;
+use serde::Serialize;
+use similar::{ChangeTag, TextDiff};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::select;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+/// Compare the local file instead of a server version
+const DIFF_LOCAL: &str = "local";
+
+/// Output the result as Json
+const DIFF_JSON: &str = "json";
+
+pub struct DiffCommand;
+
+/// Diff result of one file
+#[derive(Serialize, Clone, Debug)]
+pub struct DiffReport {
+    /// Virtual file path
+    #[serde(rename = "Path")]
+    pub path: String,
+
+    /// Old side ("v3")
+    #[serde(rename = "From")]
+    pub from: String,
+
+    /// New side ("v5" or "local")
+    #[serde(rename = "To")]
+    pub to: String,
+
+    /// Both sides have the same content
+    #[serde(rename = "Identical")]
+    pub identical: bool,
+
+    /// One of the sides is not text
+    #[serde(rename = "Binary")]
+    pub binary: bool,
+
+    #[serde(rename = "FromSize")]
+    pub from_size: u64,
+
+    #[serde(rename = "ToSize")]
+    pub to_size: u64,
+
+    #[serde(rename = "FromDigest")]
+    pub from_digest: String,
+
+    #[serde(rename = "ToDigest")]
+    pub to_digest: String,
+
+    /// Unified diff hunks (text only)
+    #[serde(rename = "Hunks")]
+    pub hunks: Vec<DiffHunk>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DiffHunk {
+    /// Hunk header ("@@ -1,3 +1,4 @@")
+    #[serde(rename = "Header")]
+    pub header: String,
+
+    #[serde(rename = "Lines")]
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Serialize, Clone, Debug)]
+pub struct DiffLine {
+    /// "+", "-" or " "
+    #[serde(rename = "Tag")]
+    pub tag: String,
+
+    #[serde(rename = "Content")]
+    pub content: String,
+}
+
+#[async_trait]
+impl Command for DiffCommand {
+
+    async fn local(&self, stream: &mut TcpStream, args: Vec<&str>) -> Option<ClientResult> {
+
+        // Sync database
+        sync_local(stream).await;
+        let database = Database::read().await;
+
+        // Load local file mapping
+        let local = LocalFileMap::read().await;
+
+        // Validate arguments <search> <old_version> <new_version|local> <json?>
+        if args.len() < 4 { return None; }
+        let inputs = args[1].split("|");
+        let json = args.get(4) == Some(&DIFF_JSON);
+
+        let mut command_result = if json {
+            ClientResult::query(ClientResultQueryProcess::direct).await
+        } else {
+            ClientResult::query(ClientResultQueryProcess::line_by_line).await
+        };
+        let mut reports = Vec::new();
+
+        for input in inputs {
+            let file = database.search_file(input.to_string());
+
+            // Fetch both sides, the messages must be exchanged even if the file is missing
+            let old_path = fetch_version(stream, file, args[2]).await;
+            let new_path = if args[3] == DIFF_LOCAL {
+                file.and_then(|file| local.file_to_path(&database, file))
+                    .filter(|path| path.exists())
+            } else {
+                fetch_version(stream, file, args[3]).await
+            };
+
+            let Some(file) = file else {
+                command_result.err(format!("File '{}' not found", input).as_str());
+                continue;
+            };
+
+            let (Some(old_path), Some(new_path)) = (old_path, new_path) else {
+                command_result.err(format!("Failed to get the contents of '{}'", file.path()).as_str());
+                continue;
+            };
+
+            let from = format!("v{}", resolve_version(file, args[2]).unwrap_or_default());
+            let to = if args[3] == DIFF_LOCAL {
+                DIFF_LOCAL.to_string()
+            } else {
+                format!("v{}", resolve_version(file, args[3]).unwrap_or_default())
+            };
+
+            match build_report(file.path(), from, to, old_path, new_path) {
+                Ok(report) => reports.push(report),
+                Err(err) => command_result.err(format!("Failed to compare '{}': {}", file.path(), err).as_str()),
+            }
+        }
+
+        if json {
+            command_result.log(serde_json::to_string(&reports).unwrap_or("[]".to_string()).as_str());
+        } else {
+            for report in &reports {
+                render_report(&mut command_result, report);
+            }
+        }
+
+        Some(command_result)
+    }
+
+    async fn remote(
+        &self,
+        stream: &mut TcpStream,
+        args: Vec<&str>,
+        (_uuid, _member): (String, &Member),
+        database: Arc<Mutex<Database>>
+    ) {
+        // First sync database, and keep the synced copy
+        let database = entry_mutex_async!(database, |guard| {
+            sync_remote(stream, guard).await;
+            guard.clone()
+        });
+
+        // Validate arguments <search> <old_version> <new_version|local>
+        if args.len() < 4 { return; }
+        let inputs = args[1].split("|");
+        let versions: Vec<&str> = args[2..4].iter()
+            .filter(|version| **version != DIFF_LOCAL)
+            .cloned()
+            .collect();
+
+        for input in inputs {
+            let file = database.search_file(input.to_string());
+
+            for version in &versions {
+                let mut success = false;
+                let mut return_message = String::new();
+
+                if let ClientMessage::Ready = read_msg::<ClientMessage>(stream).await {
+                    let server_path = file.and_then(|file| {
+                        resolve_version(file, version).and_then(|v| file.server_path_version(v))
+                    });
+                    if let Some(server_path) = server_path {
+                        match send_file(stream, server_path).await {
+                            Ok(_) => success = true,
+                            Err(err) => return_message = err.to_string(),
+                        }
+                    }
+                }
+
+                if success {
+                    send_msg(stream, &ServerMessage::Done).await;
+                } else {
+                    send_msg(stream, &ServerMessage::Deny(return_message)).await;
+                }
+            }
+        }
+    }
+}
+
+/// Resolve version text, "0" means the latest version
+fn resolve_version(file: &VirtualFile, version_str: &str) -> Option<u32> {
+    match u32::from_str(version_str) {
+        Ok(0) => Some(file.version()),
+        Ok(version) => Some(version),
+        Err(_) => None
+    }
+}
+
+/// Get the content of a server version, from the local cache if possible
+async fn fetch_version(stream: &mut TcpStream, file: Option<&VirtualFile>, version_str: &str) -> Option<PathBuf> {
+    let cache_file = file.and_then(|file| {
+        resolve_version(file, version_str).and_then(|version| local_cache_file(file, &version.to_string()))
+    });
+
+    let mut downloading = false;
+    let mut fetched = None;
+    match cache_file {
+        Some(cache_file) if cache_file.exists() => {
+            send_msg(stream, &ClientMessage::NotReady).await;
+            cache_used(&cache_file).await;
+            fetched = Some(cache_file);
+        }
+        Some(cache_file) => {
+            send_msg(stream, &ClientMessage::Ready).await;
+            downloading = true;
+            if read_file(stream, &cache_file).await.is_ok() {
+                fetched = Some(cache_file);
+            }
+        }
+        None => send_msg(stream, &ClientMessage::NotReady).await,
+    }
+
+    // Handle timeout or server response
+    let done = select! {
+        _ = sleep(Duration::from_secs(15)) => false,
+        result = read_msg::<ServerMessage>(stream) => result == ServerMessage::Done,
+    };
+
+    // Never keep a broken download in the cache
+    if downloading && !done && let Some(path) = fetched.take() {
+        let _ = tokio::fs::remove_file(path).await;
+    }
+    if let (true, Some(path), Some(file)) = (downloading, &fetched, file) {
+        cache_downloaded(path, file, resolve_version(file, version_str).unwrap_or_default()).await;
+    }
+    fetched
+}
+
+/// Compare two files
+fn build_report(path: String, from: String, to: String, old_path: PathBuf, new_path: PathBuf) -> std::io::Result<DiffReport> {
+    let old = std::fs::read(&old_path)?;
+    let new = std::fs::read(&new_path)?;
+
+    let mut report = DiffReport {
+        path,
+        from,
+        to,
+        identical: old == new,
+        binary: is_binary(&old) || is_binary(&new),
+        from_size: old.len() as u64,
+        to_size: new.len() as u64,
+        from_digest: md5_digest(old_path).unwrap_or_default(),
+        to_digest: md5_digest(new_path).unwrap_or_default(),
+        hunks: Vec::new(),
+    };
+
+    if report.identical || report.binary {
+        return Ok(report);
+    }
+
+    let old_text = String::from_utf8_lossy(&old);
+    let new_text = String::from_utf8_lossy(&new);
+    let diff = TextDiff::from_lines(old_text.as_ref(), new_text.as_ref());
+    for hunk in diff.unified_diff().context_radius(3).iter_hunks() {
+        let lines = hunk.iter_changes()
+            .map(|change| DiffLine {
+                tag: match change.tag() {
+                    ChangeTag::Delete => "-",
+                    ChangeTag::Insert => "+",
+                    ChangeTag::Equal => " ",
+                }.to_string(),
+                content: change.value().trim_end_matches(['\r', '\n']).to_string(),
+            })
+            .collect();
+        report.hunks.push(DiffHunk { header: hunk.header().to_string(), lines });
+    }
+    Ok(report)
+}
+
+/// Text files must be valid UTF-8 without NUL bytes
+fn is_binary(content: &[u8]) -> bool {
+    content.contains(&0) || std::str::from_utf8(content).is_err()
+}
+
+/// Print a report as a coloured unified diff
+fn render_report(result: &mut ClientResult, report: &DiffReport) {
+    result.log(format!("--- {} ({})", report.path, report.from).red().to_string().as_str());
+    result.log(format!("+++ {} ({})", report.path, report.to).green().to_string().as_str());
+
+    if report.identical {
+        result.log("No changes");
+        return;
+    }
+
+    if report.binary {
+        result.log(format!("Binary files differ: {} bytes ({}) -> {} bytes ({})",
+                           report.from_size, report.from_digest,
+                           report.to_size, report.to_digest).as_str());
+        return;
+    }
+
+    for hunk in &report.hunks {
+        result.log(hunk.header.cyan().to_string().as_str());
+        for line in &hunk.lines {
+            let text = format!("{}{}", line.tag, line.content);
+            match line.tag.as_str() {
+                "-" => result.log(text.red().to_string().as_str()),
+                "+" => result.log(text.green().to_string().as_str()),
+                _ => result.log(text.as_str()),
+            }
+        }
+    }
+}
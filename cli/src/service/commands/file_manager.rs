@@ -1,8 +1,12 @@
 use crate::data::database::{Database, VirtualFile};
+use crate::data::database_journal::DatabaseJournal;
 use crate::data::local_file_map::{LocalFile, LocalFileMap};
 use crate::data::member::Member;
+use crate::data::member::MemberDuty::Leader;
+use crate::data::workspace::Workspace;
 use crate::service::commands::utils_database_sync::{sync_local, sync_remote};
 use crate::service::jam_command::Command;
+use crate::service::jam_notifier::publish_write;
 use crate::service::messages::ServerMessage::{Deny, Text};
 use crate::service::service_utils::{read_msg, send_msg};
 use async_trait::async_trait;
@@ -56,39 +60,60 @@ impl Command for FileOperationCommand {
         if args[1].to_lowercase().trim() == "add" {
             let mut local = LocalFileMap::read().await;
             let database = Database::read().await;
-            let search = args[2];
-
-            if let Ok(current) = current_dir() {
-                let local_file_path_buf = current.join(process_path_text(search.to_string()));
-
-                // Handle case where local file exists
-                if local_file_path_buf.exists() {
-                    if let Some(file) = database.search_file(search.to_string()) {
-                        let file_path = file.path();
-                        if let Some(file_uuid) = database.uuid_of_path(file_path.clone()) {
-                            local.file_uuids.insert(file_path, file_uuid.clone());
-                            local.file_paths.insert(file_uuid, LocalFile {
-                                local_path: search.to_string(),
-                                local_version: file.version(),
-                                local_digest: md5_digest(local_file_path_buf).unwrap_or_default(),
-                                completed: false,
-                                completed_digest: String::new(),
-                                completed_commit: String::new(),
-                            });
+
+            for search in args[2].split("|") {
+                if let Ok(current) = current_dir() {
+                    let local_file_path_buf = current.join(process_path_text(search.to_string()));
+
+                    // Handle case where local file exists
+                    if local_file_path_buf.exists() {
+                        if let Some(file) = database.search_file(search.to_string()) {
+                            let file_path = file.path();
+                            if let Some(file_uuid) = database.uuid_of_path(file_path.clone()) {
+
+                                // Files that already existed keep their records
+                                if local.file_paths.contains_key(&file_uuid) {
+                                    continue;
+                                }
+
+                                local.file_uuids.insert(file_path, file_uuid.clone());
+                                local.file_paths.insert(file_uuid, LocalFile {
+                                    local_path: search.to_string(),
+                                    local_version: file.version(),
+                                    local_size: local_file_path_buf.metadata().map(|metadata| metadata.len()).unwrap_or_default(),
+                                    local_digest: md5_digest(local_file_path_buf).unwrap_or_default(),
+                                    completed: false,
+                                    completed_digest: String::new(),
+                                    completed_commit: String::new(),
+                                });
+                            }
                         }
                     }
-                }
-                else {
-                    // Otherwise, notify member where file should be stored
-                    command_result.warn("Virtual file created but missing locally.");
-                    command_result.log("Save completed file to:");
-                    command_result.log(format!("{}", local_file_path_buf.display().to_string().green()).as_str());
+                    else {
+                        // Otherwise, notify member where file should be stored
+                        command_result.warn("Virtual file created but missing locally.");
+                        command_result.log("Save completed file to:");
+                        command_result.log(format!("{}", local_file_path_buf.display().to_string().green()).as_str());
+                    }
                 }
             }
 
             LocalFileMap::update(&local).await;
         }
 
+        // After lock changes, local files are only writable while held
+        if matches!(args[1].to_lowercase().trim(), "get" | "get_longer" | "throw" | "mergeable" | "unmergeable") {
+            let local = LocalFileMap::read().await;
+            let database = Database::read().await;
+            let self_uuid = Workspace::read().await.client.map(|client| client.uuid).unwrap_or_default();
+
+            for search in args[2].split("|") {
+                let Some(file) = database.search_file(search.to_string()) else { continue; };
+                let Some(uuid) = database.uuid_of_path(file.path()) else { continue; };
+                let _ = local.apply_permission(&database, &uuid, &self_uuid);
+            }
+        }
+
         Some(command_result)
     }
 
@@ -96,7 +121,7 @@ impl Command for FileOperationCommand {
         &self,
         stream: &mut TcpStream,
         args: Vec<&str>,
-        (uuid, _member): (String, &Member),
+        (uuid, member): (String, &Member),
         database: Arc<Mutex<Database>>
     ) {
         // Parameter validation
@@ -121,9 +146,36 @@ impl Command for FileOperationCommand {
 
         match operation.trim() {
 
+            // Batch file addition, existing files are skipped
+            "add" if inputs.len() > 1 => {
+                entry_mutex_async!(database, |guard| {
+                    let snapshot = guard.clone();
+                    for input in &inputs {
+                        if guard.search_file(input.clone()).is_some() {
+                            fail += 1;
+                            continue;
+                        }
+                        match guard.insert_virtual_file(VirtualFile::new(input.clone())) {
+                            Ok(true) => success += 1,
+                            _ => fail += 1
+                        }
+                    }
+
+                    if success < 1 {
+                        send_msg(stream, &Deny(format!("No virtual file created ({} failed)", fail))).await;
+                    } else {
+                        write_changes(&snapshot, guard, (&uuid, member)).await;
+                        send_msg(stream, &Text(format!("Created {} virtual files ({} failed)", success, fail))).await;
+                    }
+                    sync_remote(stream, guard).await;
+                    return;
+                })
+            }
+
             // File addition
             "add" => {
                 entry_mutex_async!(database, |guard| {
+                    let snapshot = guard.clone();
                     if guard.search_file(args[2].to_string()).is_some() {
                         send_msg(stream, &Deny(format!("File '{}' already exists", args[2]))).await;
                         sync_remote(stream, guard).await;
@@ -132,9 +184,9 @@ impl Command for FileOperationCommand {
 
                     match guard.insert_virtual_file(VirtualFile::new(args[2].to_string())) {
                         Ok(true) => {
+                            write_changes(&snapshot, guard, (&uuid, member)).await;
                             send_msg(stream, &Text(format!("Created virtual file '{}'", args[2]))).await;
                             sync_remote(stream, guard).await;
-                            Database::update(guard).await;
                             return;
                         }
                         _ => {
@@ -149,6 +201,7 @@ impl Command for FileOperationCommand {
             // File removal
             "remove" => {
                 entry_mutex_async!(database, |guard| {
+                    let snapshot = guard.clone();
                     for input in inputs {
                         total += 1;
                         let path = process_path_text(input.to_string());
@@ -165,6 +218,7 @@ impl Command for FileOperationCommand {
                             Err(_) => fail += 1
                         }
                     }
+                    write_changes(&snapshot, guard, (&uuid, member)).await;
                 });
             }
 
@@ -194,16 +248,17 @@ impl Command for FileOperationCommand {
 
                 // Match, build mapping
                 let mut i = 0;
-                while i < from_count {
-                    total += 1;
-                    let def = String::default();
-                    let (from_path, to_path) =
-                        (from.get(i).unwrap_or(&def),
-                         to.get(i).unwrap_or(&def));
-                    let (from_path, to_path) = (from_path.clone(), to_path.clone());
-                    i += 1;
-
-                    entry_mutex_async!(database, |guard| {
+                entry_mutex_async!(database, |guard| {
+                    let snapshot = guard.clone();
+                    while i < from_count {
+                        total += 1;
+                        let def = String::default();
+                        let (from_path, to_path) =
+                            (from.get(i).unwrap_or(&def),
+                             to.get(i).unwrap_or(&def));
+                        let (from_path, to_path) = (from_path.clone(), to_path.clone());
+                        i += 1;
+
                         let Some(file) = guard.search_file_mut(from_path.clone()) else {
                             fail += 1;
                             continue;
@@ -229,8 +284,9 @@ impl Command for FileOperationCommand {
 
                         // If break condition reached, consider failure
                         fail += 1;
-                    });
-                }
+                    }
+                    write_changes(&snapshot, guard, (&uuid, member)).await;
+                });
             }
 
             // Rollback operation
@@ -241,9 +297,10 @@ impl Command for FileOperationCommand {
                     return;
                 }
 
-                for input in inputs {
-                    total += 1;
-                    entry_mutex_async!(database, |guard| {
+                entry_mutex_async!(database, |guard| {
+                    let snapshot = guard.clone();
+                    for input in inputs {
+                        total += 1;
 
                         // File
                         let Some(file) = guard.search_file_mut(input.to_string()) else {
@@ -269,17 +326,63 @@ impl Command for FileOperationCommand {
                             fail += 1;
                             continue;
                         }
-                    })
+                    }
+                    write_changes(&snapshot, guard, (&uuid, member)).await;
+                })
+            }
+
+            // Changeset rollback operation
+            "rollback_changeset" => {
+                entry_mutex_async!(database, |guard| {
+                    let snapshot = guard.clone();
+                    match guard.rollback_changeset(args[2], &uuid) {
+                        Ok(count) => {
+                            total += count;
+                            success += count;
+                        }
+                        Err(reason) => {
+                            send_msg(stream, &Deny(reason)).await;
+                            sync_remote(stream, guard).await;
+                            return;
+                        }
+                    }
+                    write_changes(&snapshot, guard, (&uuid, member)).await;
+                })
+            }
+
+            // Mergeable mode (Leader only)
+            "mergeable" | "unmergeable" => {
+                if !member.member_duties.contains(&Leader) {
+                    send_msg(stream, &Deny("Insufficient privileges: \"Leader\" duty required".to_string())).await;
+                    entry_mutex_async!(database, |guard| sync_remote(stream, guard).await);
+                    return;
                 }
+
+                let mergeable = operation.trim() == "mergeable";
+                entry_mutex_async!(database, |guard| {
+                    let snapshot = guard.clone();
+                    for input in inputs {
+                        total += 1;
+                        let Some(file) = guard.search_file_mut(input.to_string()) else {
+                            fail += 1;
+                            continue;
+                        };
+
+                        file.set_mergeable(mergeable);
+                        success += 1;
+                    }
+                    write_changes(&snapshot, guard, (&uuid, member)).await;
+                })
             }
 
             // File lock operations
             "get" | "get_longer" => {
                 let is_long = operation.trim() == "get_longer";
 
-                for input in inputs {
-                    total += 1;
-                    entry_mutex_async!(database, |guard| {
+                entry_mutex_async!(database, |guard| {
+                    let snapshot = guard.clone();
+                    for input in inputs {
+                        total += 1;
                         let Some(file) = guard.search_file_mut(input.to_string()) else {
                             fail += 1;
                             continue;
@@ -290,16 +393,18 @@ impl Command for FileOperationCommand {
                         } else {
                             fail += 1;
                         }
-                    })
-                }
+                    }
+                    write_changes(&snapshot, guard, (&uuid, member)).await;
+                })
             }
 
             // Release file lock operation
             "throw" => {
 
-                for input in inputs {
-                    total += 1;
-                    entry_mutex_async!(database, |guard| {
+                entry_mutex_async!(database, |guard| {
+                    let snapshot = guard.clone();
+                    for input in inputs {
+                        total += 1;
                         let Some(file) = guard.search_file_mut(input.to_string()) else {
                             fail += 1;
                             continue;
@@ -317,8 +422,9 @@ impl Command for FileOperationCommand {
                                 fail += 1;
                             }
                         }
-                    })
-                }
+                    }
+                    write_changes(&snapshot, guard, (&uuid, member)).await;
+                })
             }
 
             // Unknown operation
@@ -340,16 +446,15 @@ impl Command for FileOperationCommand {
                 sync_remote(stream, guard).await;
             });
         }
-
-        // Save if any operation succeeded
-        if success > 0 {
-            entry_mutex_async!(database, |guard| {
-                Database::update(guard).await;
-            })
-        }
     }
 }
 
+/// Write the changes of an operation to the journal before answering, and publish them as made by the member
+async fn write_changes(snapshot: &Database, database: &Database, (uuid, member): (&String, &Member)) {
+    DatabaseJournal::write(snapshot, database, uuid).await;
+    publish_write(snapshot, database, &member.member_name).await;
+}
+
 /// Check file availability (lock status)
 async fn is_available(file: &VirtualFile, stream: &mut TcpStream, self_uuid: String) -> bool {
     match file.get_locker_owner().await {
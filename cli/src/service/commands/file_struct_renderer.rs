@@ -11,6 +11,7 @@ use jam_ready::utils::local_archive::LocalArchive;
 use jam_ready::utils::text_process::{process_path_text, show_tree};
 use crate::data::client_result::{ClientResult, ClientResultQueryProcess};
 use crate::data::database::{Database, VirtualFile};
+use crate::data::ignore_rules::IgnoreRules;
 use crate::data::local_file_map::LocalFileMap;
 use crate::data::member::Member;
 use crate::data::workspace::{ClientWorkspace, Workspace};
@@ -28,6 +29,7 @@ const OTHER_LOCK_FLAG: char = 'g';
 const UNTRACKED_FLAG: char = 'n';
 const COMPLETED_FLAG: char = 'c';
 const REMOVED_FLAG: char = 'd';
+const ALL_FLAG: char = 'a';
 
 pub struct ShowFileStructCommand;
 
@@ -65,10 +67,17 @@ impl Command for ShowFileStructCommand {
             let show_untracked = switches.contains(UNTRACKED_FLAG);
             let show_completed = switches.contains(COMPLETED_FLAG);
             let show_removed = switches.contains(REMOVED_FLAG);
+            let show_all = switches.contains(ALL_FLAG);
 
             // Process workspace files
             if show_remote {
                 for file in database.files() {
+
+                    // Files outside the sparse checkout are hidden
+                    if !show_all && !client.in_sparse(&file.path()) {
+                        continue;
+                    }
+
                     if let Some(info) = build_remote_file_info(
                         &file, &database, &local, &client,
                         show_zero_version, show_updated, show_other, show_moved,
@@ -211,6 +220,7 @@ fn get_local_file_info(
 ) -> Vec<String> {
     let mut paths : Vec<String> = Vec::new();
     let workspace_root = env!("PATH_WORKSPACE_ROOT");
+    let ignore_rules = IgnoreRules::load();
 
     for path in get_all_file_paths() {
 
@@ -249,7 +259,7 @@ fn get_local_file_info(
                     }
                 }
             }
-            None if show_untracked => {
+            None if show_untracked && !ignore_rules.is_ignored(&path) => {
                 // Untracked file
                 paths.push(format!(
                     "{} {}",
@@ -264,7 +274,7 @@ fn get_local_file_info(
     paths
 }
 
-fn get_all_file_paths() -> Vec<String> {
+pub fn get_all_file_paths() -> Vec<String> {
     WalkDir::new(".")
         .into_iter()
         .filter_map(Result::ok)
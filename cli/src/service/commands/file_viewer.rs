@@ -1,10 +1,13 @@
 use crate::data::client_result::ClientResult;
 use crate::data::database::{Database, VirtualFile};
-use crate::data::local_file_map::{LocalFile, LocalFileMap};
+use crate::data::local_cache::{cache_downloaded, cache_used};
+use crate::data::local_file_map::{set_read_only, LocalFile, LocalFileMap};
 use crate::data::member::Member;
+use crate::data::workspace::Workspace;
 use crate::service::commands::utils_database_sync::{sync_local, sync_remote};
 use crate::service::commands::utils_file_transmitter::{read_file, send_file};
 use crate::service::jam_command::Command;
+use crate::service::jam_peer::{fetch_from_peers, PeerFetch};
 use crate::service::messages::{ClientMessage, ServerMessage};
 use crate::service::service_utils::{read_msg, send_msg};
 use async_trait::async_trait;
@@ -37,6 +40,12 @@ impl Command for ViewCommand {
 
         // Load local file mapping
         let mut local = LocalFileMap::read().await;
+        let client = Workspace::read().await.client;
+        let self_uuid = client.as_ref().map(|client| client.uuid.clone()).unwrap_or_default();
+        let workspace_name = client.map(|client| client.workspace_name).unwrap_or_default();
+
+        // Stop asking peers once nobody answered
+        let mut ask_peers = true;
 
         // Validate arguments
         if args.len() < 2 { return None; } // <search>
@@ -53,6 +62,10 @@ impl Command for ViewCommand {
             if let Some(file) = database.search_file(file_path_str.clone()) {
                 if let Some(client_path) = local.file_to_path(&database, file) {
                     let mut ready = true;
+                    let version = match u32::from_str(view_version) {
+                        Ok(0) | Err(_) => file.version(),
+                        Ok(version) => version,
+                    };
 
                     // Check if file exists locally and matches server version
                     if let Some(local_uuid) = database.uuid_of_path(file.path()) {
@@ -67,42 +80,73 @@ impl Command for ViewCommand {
                         }
                     }
 
-                    // Check local cache files
+                    // Read-only files are replaced as well
                     if ready {
-                        if let Some(cache_file) = local_cache_file(file, view_version) {
-                            if cache_file.exists() {
-                                match copy_file(&cache_file, &client_path.clone()) {
-                                    Ok(_) => {
-                                        print_msg = "File download completed! (from local cache)".to_string();
-                                        success = true;
-                                        ready = false;
-
-                                        let uuid = database.uuid_of_path(file.path()).unwrap_or("".to_string());
-                                        let local_path_str = if let Some(local_file) = local.search_to_local(&database, file.path()) {
-                                            local_file.local_path.clone()
-                                        } else {
-                                            file.path()
-                                        };
-
-                                        generate_local_file_map_info(&mut local, file, client_path.clone(), local_path_str, uuid, view_version);
-
-                                        send_msg(stream, &ClientMessage::NotReady).await;
-                                    }
-                                    Err(_) => {
-                                        success = false;
-                                        ready = true;
-                                        // Not ready, trying to download
-                                    }
+                        let _ = set_read_only(&client_path, false);
+                    }
+
+                    // Check local cache files
+                    if ready
+                        && let Some(cache_file) = local_cache_file(file, &version.to_string())
+                        && cache_file.exists() {
+                        match copy_file(&cache_file, &client_path.clone()) {
+                            Ok(_) => {
+                                cache_used(&cache_file).await;
+                                print_msg = "File download completed! (from local cache)".to_string();
+                                success = true;
+                                ready = false;
+
+                                let uuid = database.uuid_of_path(file.path()).unwrap_or("".to_string());
+                                let local_path_str = if let Some(local_file) = local.search_to_local(&database, file.path()) {
+                                    local_file.local_path.clone()
+                                } else {
+                                    file.path()
+                                };
+
+                                generate_local_file_map_info(&mut local, file, client_path.clone(), local_path_str, uuid, view_version);
+
+                                send_msg(stream, &ClientMessage::NotReady).await;
+                            }
+                            Err(_) => {
+                                success = false;
+                                ready = true;
+                                // Not ready, trying to download
+                            }
+                        }
+                    }
+
+                    // Try nearby peers holding the same version
+                    if ready && ask_peers && let Some(digest) = file.version_digest(version) {
+                        match fetch_from_peers(&workspace_name, &digest, &client_path).await {
+                            PeerFetch::Received => {
+                                print_msg = "File download completed! (from peer)".to_string();
+                                success = true;
+                                ready = false;
+
+                                let uuid = database.uuid_of_path(file.path()).unwrap_or("".to_string());
+                                let local_path_str = if let Some(local_file) = local.search_to_local(&database, file.path()) {
+                                    local_file.local_path.clone()
+                                } else {
+                                    file.path()
+                                };
+                                generate_local_file_map_info(&mut local, file, client_path.clone(), local_path_str, uuid, view_version);
+
+                                if let Some(path) = local_cache_file(file, &version.to_string()) && ! path.exists() && copy_file(&client_path.clone(), &path).is_ok() {
+                                    cache_downloaded(&path, file, version).await;
                                 }
+
+                                send_msg(stream, &ClientMessage::NotReady).await;
                             }
+                            PeerFetch::NoPeers => ask_peers = false,
+                            PeerFetch::Missing => {}
                         }
                     }
 
                     if ready {
                         send_msg(stream, &ClientMessage::Ready).await;
 
-                        match read_file(stream, client_path.clone()).await {
-                            Ok(_) => {
+                        match read_file(stream, client_path.clone()).await.ok() {
+                            Some(_) => {
                                 let local_path_buf = match local.search_to_path_relative(&database, file.path()) {
                                     Some(p) => p,
                                     None => PathBuf::from_str(file.path().as_str()).unwrap(),
@@ -116,18 +160,21 @@ impl Command for ViewCommand {
                                 success = true;
 
                                 // Attempting to establish cache
-                                if let Some(path) = local_cache_file(file, view_version) {
-                                    if ! path.exists() {
-                                        let _ = copy_file(&client_path.clone(), &path);
-                                    }
+                                if let Some(path) = local_cache_file(file, &version.to_string()) && ! path.exists() && copy_file(&client_path.clone(), &path).is_ok() {
+                                    cache_downloaded(&path, file, version).await;
                                 }
                             }
-                            Err(_) => {
+                            None => {
                                 print_msg = "File download failed".to_string();
                             }
                         }
                     }
                 }
+
+                // Protect files held by other members
+                if success && let Some(uuid) = database.uuid_of_path(file.path()) {
+                    let _ = local.apply_permission(&database, &uuid, &self_uuid);
+                }
             }
 
             // Handle timeout or server response
@@ -160,13 +207,12 @@ impl Command for ViewCommand {
         (_uuid, _member): (String, &Member),
         database: Arc<Mutex<Database>>
     ) {
-        // First sync database
-        entry_mutex_async!(database, |guard| {
+        // First sync database, and keep the synced copy (the file on disk may lag behind the journal)
+        let database = entry_mutex_async!(database, |guard| {
             sync_remote(stream, guard).await;
+            guard.clone()
         });
 
-        let database = Database::read().await;
-
         // Validate arguments
         if args.len() < 2 { return; } // <search>
         let inputs = args[1].split("|");
@@ -210,10 +256,12 @@ impl Command for ViewCommand {
     }
 }
 
-fn local_cache_file(virtual_path: &VirtualFile, version_str: &str) -> Option<PathBuf> {
+pub fn local_cache_file(virtual_path: &VirtualFile, version_str: &str) -> Option<PathBuf> {
     let Ok(current_dir) = current_dir() else { return None };
     let Ok(version) = u32::from_str(version_str) else { return None };
     if let Some(path) = virtual_path.real_path_version(version) {
+        // Version 0 has no content
+        if path.as_os_str().is_empty() { return None }
         return Some(current_dir.join(env!("PATH_CACHE")).join(path))
     }
     None
@@ -229,6 +277,7 @@ fn generate_local_file_map_info(
         } else {
             file.version()
         },
+        local_size: client_path.metadata().map(|metadata| metadata.len()).unwrap_or_default(),
         local_digest: md5_digest(client_path).unwrap_or_default(),
         completed: false,
         completed_digest: String::new(),
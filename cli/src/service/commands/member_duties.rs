@@ -0,0 +1,47 @@
+use crate::data::client_result::{ClientResult, ClientResultQueryProcess};
+use crate::data::database::Database;
+use crate::data::member::Member;
+use crate::service::jam_command::Command;
+use crate::service::messages::ServerMessage;
+use crate::service::messages::ServerMessage::Text;
+use crate::service::service_utils::{read_msg, send_msg};
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+/// Metadata key of the duty list, e.g. "Leader,Developer"
+pub const DUTIES_METADATA: &str = "duties";
+
+/// Duties of the current member, used to preview operations limited to some duties
+pub struct DutiesCommand;
+
+#[async_trait]
+impl Command for DutiesCommand {
+    async fn local(&self, stream: &mut TcpStream, _args: Vec<&str>) -> Option<ClientResult> {
+        let mut command_result = ClientResult::query(ClientResultQueryProcess::comma).await;
+
+        let Text(duties) = read_msg::<ServerMessage>(stream).await else {
+            return None;
+        };
+        for duty in duties.split(',').filter(|duty| !duty.is_empty()) {
+            command_result.log(duty);
+        }
+        command_result.metadata(DUTIES_METADATA.to_string(), duties);
+        Some(command_result)
+    }
+
+    async fn remote(
+        &self,
+        stream: &mut TcpStream,
+        _args: Vec<&str>,
+        (_uuid, member): (String, &Member),
+        _database: Arc<Mutex<Database>>
+    ) {
+        let duties = member.member_duties.iter()
+            .map(|duty| format!("{:?}", duty))
+            .collect::<Vec<String>>()
+            .join(",");
+        send_msg(stream, &Text(duties)).await;
+    }
+}
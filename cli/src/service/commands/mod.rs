@@ -1,20 +1,27 @@
 mod utils_database_sync;
 mod utils_duty_verifier;
-mod utils_file_transmitter;
+pub mod utils_file_transmitter;
+mod utils_file_merger;
 
 mod archive;
 mod commiter;
 mod file_manager;
 mod file_struct_renderer;
 mod workspace_update;
+pub mod workspace_status;
 mod file_viewer;
+mod file_differ;
+pub mod member_duties;
 
 use crate::service::commands::archive::ArchiveCommand;
 use crate::service::commands::commiter::CommitCommand;
 use crate::service::commands::file_manager::FileOperationCommand;
 use crate::service::commands::workspace_update::UpdateCommand;
+use crate::service::commands::workspace_status::StatusCommand;
 use crate::service::commands::file_struct_renderer::ShowFileStructCommand;
 use crate::service::commands::file_viewer::ViewCommand;
+use crate::service::commands::file_differ::DiffCommand;
+use crate::service::commands::member_duties::DutiesCommand;
 use crate::service::jam_command::CommandRegistry;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -25,9 +32,12 @@ pub fn registry() -> CommandRegistry {
     // Core commands
     registry.insert("update", Arc::new(UpdateCommand));
     registry.insert("view", Arc::new(ViewCommand));
+    registry.insert("diff", Arc::new(DiffCommand));
     registry.insert("commit", Arc::new(CommitCommand));
     registry.insert("file", Arc::new(FileOperationCommand));
     registry.insert("struct", Arc::new(ShowFileStructCommand));
+    registry.insert("status", Arc::new(StatusCommand));
+    registry.insert("duties", Arc::new(DutiesCommand));
 
     // Debug commands
     registry.insert("archive", Arc::new(ArchiveCommand));
@@ -1,3 +1,4 @@
+use crate::data::client_result::report_err;
 use crate::data::member::{Member, MemberDuty};
 use crate::service::messages::ServerMessage::{Deny, Pass};
 use crate::service::service_utils::{read_msg, send_msg};
@@ -8,7 +9,7 @@ pub async fn verify(stream: &mut TcpStream) -> bool {
     match read_msg(stream).await {
         Pass => true,
         Deny(message) => {
-            eprintln!("Authentication failed: {}", message);
+            report_err(format!("Authentication failed: {}", message).as_str());
             false
         }
         _ => false
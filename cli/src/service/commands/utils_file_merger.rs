@@ -0,0 +1,136 @@
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+
+/// A change made by one side to a range of base lines
+struct MergeHunk {
+    /// First replaced base line
+    start: usize,
+
+    /// End of replaced base lines (exclusive)
+    end: usize,
+
+    /// New lines
+    lines: Vec<String>,
+
+    /// Change comes from "ours"
+    ours: bool,
+}
+
+/// Three-way merge of text files by line
+/// Returns None if both sides changed the same lines differently
+pub fn three_way_merge(base: &str, ours: &str, theirs: &str) -> Option<String> {
+    if ours == theirs || base == theirs { return Some(ours.to_string()); }
+    if base == ours { return Some(theirs.to_string()); }
+
+    let base_lines: Vec<&str> = base.split_inclusive('\n').collect();
+    let ours_lines: Vec<&str> = ours.split_inclusive('\n').collect();
+    let theirs_lines: Vec<&str> = theirs.split_inclusive('\n').collect();
+
+    let mut hunks = collect_hunks(&base_lines, &ours_lines, true);
+    hunks.extend(collect_hunks(&base_lines, &theirs_lines, false));
+    hunks.sort_by_key(|hunk| (hunk.start, hunk.end));
+
+    let mut result = String::new();
+    let mut position = 0;
+    let mut index = 0;
+    while index < hunks.len() {
+
+        // Group touching or overlapping hunks
+        let group_start = hunks[index].start;
+        let mut group_end = hunks[index].end;
+        let mut next = index + 1;
+        while next < hunks.len() && hunks[next].start <= group_end {
+            group_end = group_end.max(hunks[next].end);
+            next += 1;
+        }
+        let group = &hunks[index..next];
+
+        // Unchanged lines before the group
+        result.push_str(&base_lines[position..group_start].concat());
+
+        let ours_text = apply_hunks(&base_lines, group_start, group_end, group, true);
+        let theirs_text = apply_hunks(&base_lines, group_start, group_end, group, false);
+        if group.iter().all(|hunk| hunk.ours) {
+            result.push_str(&ours_text);
+        } else if group.iter().all(|hunk| !hunk.ours) || ours_text == theirs_text {
+            result.push_str(&theirs_text);
+        } else {
+            // Both sides changed the same lines
+            return None;
+        }
+
+        position = group_end;
+        index = next;
+    }
+    result.push_str(&base_lines[position..].concat());
+
+    Some(result)
+}
+
+/// Collect the changes from base to other
+fn collect_hunks(base: &[&str], other: &[&str], ours: bool) -> Vec<MergeHunk> {
+    capture_diff_slices(Algorithm::Myers, base, other)
+        .into_iter()
+        .filter_map(|op| {
+            let (tag, old_range, new_range) = op.as_tag_tuple();
+            if tag == DiffTag::Equal {
+                return None;
+            }
+            Some(MergeHunk {
+                start: old_range.start,
+                end: old_range.end,
+                lines: other[new_range].iter().map(|line| line.to_string()).collect(),
+                ours,
+            })
+        })
+        .collect()
+}
+
+/// Apply the hunks of one side to a range of base lines
+fn apply_hunks(base: &[&str], start: usize, end: usize, group: &[MergeHunk], ours: bool) -> String {
+    let mut text = String::new();
+    let mut position = start;
+    for hunk in group.iter().filter(|hunk| hunk.ours == ours) {
+        text.push_str(&base[position..hunk.start].concat());
+        text.push_str(&hunk.lines.concat());
+        position = hunk.end;
+    }
+    text.push_str(&base[position..end].concat());
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::three_way_merge;
+
+    const BASE: &str = "first\nsecond\nthird\nfourth\nfifth\n";
+
+    #[test]
+    fn merges_edits_on_different_lines() {
+        let ours = "FIRST\nsecond\nthird\nfourth\nfifth\n";
+        let theirs = "first\nsecond\nthird\nfourth\nFIFTH\n";
+        assert_eq!(three_way_merge(BASE, ours, theirs).as_deref(), Some("FIRST\nsecond\nthird\nfourth\nFIFTH\n"));
+    }
+
+    #[test]
+    fn merges_identical_edits_once() {
+        let ours = "first\nSECOND\nthird\nfourth\nfifth\n";
+        let theirs = "first\nSECOND\nthird\nfourth\nFIFTH\n";
+        assert_eq!(three_way_merge(BASE, ours, theirs).as_deref(), Some("first\nSECOND\nthird\nfourth\nFIFTH\n"));
+        assert_eq!(three_way_merge(BASE, ours, ours).as_deref(), Some(ours));
+    }
+
+    #[test]
+    fn reports_overlapping_edits_as_conflict() {
+        let ours = "first\nsecond\nOURS\nfourth\nfifth\n";
+        let theirs = "first\nsecond\nTHEIRS\nfourth\nfifth\n";
+        assert_eq!(three_way_merge(BASE, ours, theirs), None);
+    }
+
+    #[test]
+    fn merges_insert_at_end_of_file() {
+        let ours = "first\nsecond\nthird\nfourth\nfifth\nsixth\n";
+        let theirs = "FIRST\nsecond\nthird\nfourth\nfifth\n";
+        assert_eq!(three_way_merge(BASE, ours, theirs).as_deref(), Some("FIRST\nsecond\nthird\nfourth\nfifth\nsixth\n"));
+        assert_eq!(three_way_merge(BASE, theirs, ours).as_deref(), Some("FIRST\nsecond\nthird\nfourth\nfifth\nsixth\n"));
+    }
+}
@@ -1,9 +1,14 @@
+use std::env::current_dir;
 use std::path::Path;
+use std::sync::Mutex;
 use std::time::Duration;
 use indicatif::{ProgressBar, ProgressStyle};
+use jam_ready::utils::text_process::process_path_text;
+use serde::Serialize;
 use tokio::fs::{File, OpenOptions};
 use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, BufWriter};
 use tokio::net::TcpStream;
+use tokio::sync::mpsc::UnboundedSender;
 use tokio::time::Instant;
 use crate::data::client_result::ClientResult;
 
@@ -11,6 +16,59 @@ const CHUNK_SIZE: usize = 8 * 1024;
 const PROGRESS_UPDATE_THRESHOLD: u64 = 256 * 1024;
 const PROGRESS_UPDATE_INTERVAL: Duration = Duration::from_millis(350);
 
+/// Listener of transfer progress (linker clients), None when nobody listens
+static PROGRESS_SINK: Mutex<Option<UnboundedSender<TransferProgress>>> = Mutex::new(None);
+
+/// Progress of a file transfer
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct TransferProgress {
+    /// Transfer direction
+    #[serde(rename = "Operation")]
+    pub operation: TransferOperation,
+
+    /// Local file path
+    #[serde(rename = "Path")]
+    pub path: String,
+
+    /// Transferred bytes
+    #[serde(rename = "Bytes")]
+    pub bytes: u64,
+
+    /// File size
+    #[serde(rename = "Total")]
+    pub total: u64,
+}
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum TransferOperation {
+    /// Sending a file
+    #[serde(rename = "Upload")]
+    Upload,
+
+    /// Receiving a file
+    #[serde(rename = "Download")]
+    Download,
+}
+
+/// Send the progress of the following transfers to the listener (None to stop)
+pub fn set_progress_sink(sink: Option<UnboundedSender<TransferProgress>>) {
+    *PROGRESS_SINK.lock().unwrap() = sink;
+}
+
+fn report_progress(operation: TransferOperation, path: &Path, bytes: u64, total: u64) {
+    let sink = PROGRESS_SINK.lock().unwrap();
+    let Some(sink) = sink.as_ref() else { return; };
+    let path = current_dir().ok()
+        .and_then(|current| path.strip_prefix(current).ok().map(|relative| relative.to_path_buf()))
+        .unwrap_or(path.to_path_buf());
+    let _ = sink.send(TransferProgress {
+        operation,
+        path: process_path_text(path.display().to_string()),
+        bytes,
+        total,
+    });
+}
+
 /// Sends a file over TCP with progress tracking
 pub async fn send_file(
     stream: &mut TcpStream,
@@ -53,6 +111,7 @@ pub async fn send_file(
     stream.write_all(&file_size.to_be_bytes()).await?;
 
     // Transfer file content
+    report_progress(TransferOperation::Upload, path, 0, file_size);
     let mut reader = BufReader::with_capacity(CHUNK_SIZE, &mut file);
     let mut bytes_sent = 0;
     let mut last_update = Instant::now();
@@ -73,6 +132,7 @@ pub async fn send_file(
             last_update.elapsed() >= PROGRESS_UPDATE_INTERVAL
         {
             progress_bar.set_position(bytes_sent);
+            report_progress(TransferOperation::Upload, path, bytes_sent, file_size);
             last_bytes = bytes_sent;
             last_update = Instant::now();
         }
@@ -96,6 +156,7 @@ pub async fn send_file(
         return Err("Receiver verification failed".into());
     }
 
+    report_progress(TransferOperation::Upload, path, bytes_sent, file_size);
     progress_bar.finish_with_message(format!("Sent {} bytes", bytes_sent));
     Ok(())
 }
@@ -150,6 +211,7 @@ pub async fn read_file(
     };
 
     // Receive file content
+    report_progress(TransferOperation::Download, path, 0, file_size);
     let mut buffer = vec![0u8; CHUNK_SIZE];
     let mut bytes_received = 0;
     let mut last_update = Instant::now();
@@ -168,6 +230,7 @@ pub async fn read_file(
         {
             writer.flush().await?;
             progress_bar.set_position(bytes_received);
+            report_progress(TransferOperation::Download, path, bytes_received, file_size);
             last_bytes = bytes_received;
             last_update = Instant::now();
         }
@@ -190,6 +253,7 @@ pub async fn read_file(
     stream.write_all(&[1]).await?;
     stream.flush().await?;
 
+    report_progress(TransferOperation::Download, path, bytes_received, file_size);
     progress_bar.finish_with_message(format!("Received {} bytes", bytes_received));
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,223 @@
+use crate::data::client_result::{ClientResult, ClientResultQueryProcess};
+use crate::data::database::Database;
+use crate::data::ignore_rules::IgnoreRules;
+use crate::data::local_file_map::LocalFileMap;
+use crate::data::member::Member;
+use crate::data::workspace::Workspace;
+use crate::service::commands::file_struct_renderer::get_all_file_paths;
+use crate::service::commands::utils_database_sync::{sync_local, sync_remote};
+use crate::service::jam_command::Command;
+use async_trait::async_trait;
+use colored::Colorize;
+use jam_ready::entry_mutex_async;
+use jam_ready::utils::file_digest::md5_digest;
+use jam_ready::utils::local_archive::LocalArchive;
+use serde::Serialize;
+use std::env::current_dir;
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+/// Output the result as Json
+const STATUS_JSON: &str = "json";
+
+pub struct StatusCommand;
+
+/// Working copy status, grouped by what needs to be done
+#[derive(Serialize, Default, Clone, Debug)]
+pub struct WorkspaceStatus {
+    /// Modified, and can be committed once completed
+    #[serde(rename = "Modified")]
+    pub modified: Vec<String>,
+
+    /// Modified, but locked by nobody (or by someone else)
+    #[serde(rename = "ModifiedUnlocked")]
+    pub modified_unlocked: Vec<String>,
+
+    /// Completed and ready to commit
+    #[serde(rename = "Completed")]
+    pub completed: Vec<String>,
+
+    /// Older than the server version
+    #[serde(rename = "Outdated")]
+    pub outdated: Vec<String>,
+
+    /// Moved on the server (Local path, Server path)
+    #[serde(rename = "Moved")]
+    pub moved: Vec<(String, String)>,
+
+    /// Moved or renamed locally (Old local path, New local path)
+    #[serde(rename = "Renamed")]
+    pub renamed: Vec<(String, String)>,
+
+    /// Local files without a virtual file
+    #[serde(rename = "Untracked")]
+    pub untracked: Vec<String>,
+
+    /// Removed on the server, but still existing locally
+    #[serde(rename = "Removed")]
+    pub removed: Vec<String>,
+}
+
+#[async_trait]
+impl Command for StatusCommand {
+
+    async fn local(&self, stream: &mut TcpStream, args: Vec<&str>) -> Option<ClientResult> {
+
+        sync_local(stream).await;
+
+        let json = args.get(1) == Some(&STATUS_JSON);
+        let mut command_result = if json {
+            ClientResult::query(ClientResultQueryProcess::direct).await
+        } else {
+            ClientResult::query(ClientResultQueryProcess::line_by_line).await
+        };
+
+        let client = Workspace::read().await.client?;
+        let database = Database::read().await;
+        let local = LocalFileMap::read().await;
+        let status = collect_status(&database, &local, client.uuid.trim());
+
+        if json {
+            command_result.log(serde_json::to_string(&status).unwrap_or("{}".to_string()).as_str());
+        } else {
+            render_status(&mut command_result, &status);
+        }
+        Some(command_result)
+    }
+
+    async fn remote(&self, stream: &mut TcpStream, _args: Vec<&str>, _member: (String, &Member), database: Arc<Mutex<Database>>) {
+        entry_mutex_async!(database, |guard| {
+            sync_remote(stream, guard).await;
+        });
+    }
+}
+
+/// Compare the working copy with the database
+pub fn collect_status(database: &Database, local: &LocalFileMap, self_uuid: &str) -> WorkspaceStatus {
+    let mut status = WorkspaceStatus::default();
+    let Ok(current_dir) = current_dir() else { return status; };
+
+    // Tracked files
+    for file in database.files() {
+        if file.path().is_empty() {
+            continue;
+        }
+
+        let Some(uuid) = database.uuid_of_path(file.path()) else { continue; };
+        let Some(local_file) = local.file_paths.get(&uuid) else { continue; };
+        let local_path = current_dir.join(&local_file.local_path);
+        if !local_path.exists() {
+            continue;
+        }
+
+        // Moved on the server
+        if local_file.local_path != file.path() {
+            status.moved.push((local_file.local_path.clone(), file.path()));
+        }
+
+        // Server has a newer version
+        if local_file.local_version < file.version() {
+            status.outdated.push(file.path());
+        }
+
+        // Modified locally
+        let Ok(digest) = md5_digest(local_path) else { continue; };
+        if digest == local_file.local_digest {
+            continue;
+        }
+
+        let committable = file.get_locker_owner_uuid()
+            .map(|owner| owner.trim() == self_uuid)
+            .unwrap_or(false) || file.is_mergeable_unlocked();
+
+        if !committable {
+            status.modified_unlocked.push(file.path());
+        } else if local_file.completed && local_file.completed_digest == digest {
+            status.completed.push(file.path());
+        } else {
+            status.modified.push(file.path());
+        }
+    }
+
+    // Moved or renamed locally
+    for (_, from, to) in local.detect_moved_files(database) {
+        status.renamed.push((from, to));
+    }
+
+    // Local files
+    let workspace_root = env!("PATH_WORKSPACE_ROOT");
+    let ignore_rules = IgnoreRules::load();
+    for path in get_all_file_paths() {
+
+        // Skip workspace config directory
+        if path.starts_with(workspace_root) {
+            continue;
+        }
+
+        match local.file_uuids.get(&path) {
+            Some(uuid) => {
+                let removed = database.file_with_uuid(uuid.clone())
+                    .map(|file| file.path().is_empty())
+                    .unwrap_or(true);
+                if removed {
+                    status.removed.push(path);
+                }
+            }
+            None => {
+                if !ignore_rules.is_ignored(&path) && !status.renamed.iter().any(|(_, to)| to == &path) {
+                    status.untracked.push(path);
+                }
+            }
+        }
+    }
+
+    status
+}
+
+/// Print the status groups with the suggested next command
+fn render_status(result: &mut ClientResult, status: &WorkspaceStatus) {
+    let mut empty = true;
+
+    let mut group = |result: &mut ClientResult, title: &str, hint: String, paths: Vec<String>| {
+        if paths.is_empty() {
+            return;
+        }
+        empty = false;
+        result.log(format!("{} ({})", title, paths.len()).as_str());
+        result.log(format!("  {}", hint).truecolor(128, 128, 128).to_string().as_str());
+        for path in paths {
+            result.log(format!("    {}", path).as_str());
+        }
+        result.log("");
+    };
+
+    group(result, &"Completed, ready to commit".bright_green().to_string(),
+          "jam commit".to_string(),
+          status.completed.clone());
+    group(result, &"Modified".green().to_string(),
+          "jam complete <FROM_SEARCH> <INFO>".to_string(),
+          status.modified.clone());
+    group(result, &"Modified, but not locked".bright_yellow().to_string(),
+          "jam get <FROM_SEARCH>".to_string(),
+          status.modified_unlocked.clone());
+    group(result, &"Outdated".bright_red().to_string(),
+          "jam view <FROM_SEARCH>".to_string(),
+          status.outdated.clone());
+    group(result, &"Moved on the server".yellow().to_string(),
+          "jam move <LOCAL_PATH> <SERVER_PATH> --local".to_string(),
+          status.moved.iter().map(|(local, remote)| format!("{} -> {}", local, remote)).collect());
+    group(result, &"Moved or renamed locally".yellow().to_string(),
+          "jam renames --apply".to_string(),
+          status.renamed.iter().map(|(from, to)| format!("{} -> {}", from, to)).collect());
+    group(result, &"Untracked".cyan().to_string(),
+          "jam add <PATH>".to_string(),
+          status.untracked.clone());
+    group(result, &"Removed on the server".red().to_string(),
+          "Delete the local files if they are no longer needed".to_string(),
+          status.removed.clone());
+
+    if empty {
+        result.log("Nothing to do, the workspace is up to date.");
+    }
+}
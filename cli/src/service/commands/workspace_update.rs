@@ -1,4 +1,4 @@
-use crate::data::client_result::ClientResult;
+use crate::data::client_result::{ClientResult, ClientResultQueryProcess};
 use crate::data::database::Database;
 use crate::data::member::Member;
 use crate::service::commands::utils_database_sync::{sync_local, sync_local_with_progress, sync_remote_with_progress};
@@ -9,12 +9,21 @@ use std::sync::Arc;
 use tokio::net::TcpStream;
 use tokio::sync::Mutex;
 
+/// Sync without any output
+const UPDATE_QUIET: &str = "quiet";
+
 pub struct UpdateCommand;
 
 #[async_trait]
 impl Command for UpdateCommand {
 
-    async fn local(&self, stream: &mut TcpStream, _args: Vec<&str>) -> Option<ClientResult> {
+    async fn local(&self, stream: &mut TcpStream, args: Vec<&str>) -> Option<ClientResult> {
+
+        // Background sync (watcher)
+        if args.get(1) == Some(&UPDATE_QUIET) {
+            sync_local(stream).await;
+            return Some(ClientResult::query(ClientResultQueryProcess::direct).await);
+        }
 
         let debug = ClientResult::debug_mode().await;
         let mut command_result = ClientResult::result().await;
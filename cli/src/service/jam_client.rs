@@ -1,29 +1,69 @@
+use std::io::ErrorKind;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::str::FromStr;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
 use serde::ser::StdError;
 use tokio::net::{TcpStream, UdpSocket};
+use tokio::{pin, select, spawn};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+use tokio::sync::Mutex;
+use tokio::time::{sleep, timeout};
 use jam_ready::connect_once;
 use jam_ready::utils::local_archive::LocalArchive;
-use crate::data::client_result::ClientResult;
+use crate::data::client_result::{report_err, ClientResult};
 use crate::data::workspace::{ClientWorkspace, Workspace};
 use crate::service::commands::registry;
 use crate::service::jam_command::execute_local_command;
+use crate::service::jam_notifier::ServerEvent;
 use crate::service::messages::ServerMessage;
-use crate::service::messages::ClientMessage::{Command, Verify};
-use crate::service::messages::ServerMessage::Uuid;
-use crate::service::service_utils::{read_msg, send_msg};
+use crate::service::messages::ClientMessage::{Command, Subscribe, Verify};
+use crate::service::messages::ServerMessage::{Pass, Uuid};
+use crate::service::service_utils::{read_msg, send_msg, MessageReader};
+
+/// Time to wait for the server to accept a subscription (older servers never answer)
+const SUBSCRIBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Server session held by the shell, None while every command opens its own connection
+static SESSION: Mutex<Option<Session>> = Mutex::const_new(None);
+
+/// Verified connection reused by several commands
+#[derive(Default)]
+struct Session {
+    stream: Option<TcpStream>,
+}
+
+/// Keep the verified connection open between commands
+pub async fn begin_session() {
+    SESSION.lock().await.get_or_insert_with(Session::default);
+}
+
+/// Close the held connection, every command opens its own connection again
+pub async fn end_session() {
+    SESSION.lock().await.take();
+}
+
+/// Check if the held connection is still open, without consuming server messages
+fn is_session_alive(stream: &TcpStream) -> bool {
+    let mut buf = [0u8; 1];
+    matches!(stream.try_read(&mut buf), Err(err) if err.kind() == ErrorKind::WouldBlock)
+}
 
 /// Execute command
 pub async fn execute(command_input: Vec<String>) -> Option<ClientResult> {
     let mut workspace = Workspace::read().await;
     let mut result = None;
+    let mut session = SESSION.lock().await;
 
     if let Some(client) = &mut workspace.client {
         // Try to connect using target address
         let addr = client.target_addr;
 
-        // Connect, verify and get stream
-        let stream = try_verify_connection(addr, client).await;
+        // Reuse the held connection, or connect, verify and get stream
+        let stream = match session.as_mut().and_then(|session| session.stream.take()) {
+            Some(stream) if is_session_alive(&stream) => Some(stream),
+            _ => try_verify_connection(addr, client).await,
+        };
 
         let mut args_input = Vec::new();
         for arg in command_input.iter() {
@@ -39,6 +79,13 @@ pub async fn execute(command_input: Vec<String>) -> Option<ClientResult> {
 
             // Execute command
             result = execute_local_command(&registry(), &mut stream, args).await;
+
+            // Hold the connection for the next command
+            if let Some(session) = session.as_mut() {
+                session.stream = Some(stream);
+            }
+        } else {
+            report_err(format!("Unable to connect to workspace \"{}\" ({})", client.workspace_name, addr).as_str());
         }
     }
 
@@ -46,6 +93,37 @@ pub async fn execute(command_input: Vec<String>) -> Option<ClientResult> {
     result
 }
 
+/// Receive server events on a connection of its own
+/// Returns None if the server cannot be reached or does not push events
+pub async fn subscribe_server_events() -> Option<UnboundedReceiver<ServerEvent>> {
+    let mut workspace = Workspace::read().await;
+    let client = workspace.client.as_mut()?;
+    let mut stream = try_verify_connection(client.target_addr, client).await?;
+    send_msg(&mut stream, &Subscribe).await;
+
+    let mut reader = MessageReader::default();
+    let accepted = timeout(SUBSCRIBE_TIMEOUT, reader.next::<ServerMessage>(&mut stream)).await;
+    if !matches!(accepted, Ok(Some(Pass))) {
+        return None;
+    }
+
+    // Forward events until the server or the receiver goes away
+    let (sender, receiver) = unbounded_channel();
+    spawn(async move {
+        loop {
+            select! {
+                message = reader.next::<ServerMessage>(&mut stream) => match message {
+                    Some(ServerMessage::Event(event)) => { let _ = sender.send(event); }
+                    Some(_) => continue,
+                    None => break,
+                },
+                _ = sender.closed() => break,
+            }
+        }
+    });
+    Some(receiver)
+}
+
 async fn try_verify_connection(addr: SocketAddr, client: &mut ClientWorkspace) -> Option<TcpStream> {
     connect_once!(addr, |stream| {
         // Send login code for verification
@@ -62,7 +140,7 @@ async fn try_verify_connection(addr: SocketAddr, client: &mut ClientWorkspace) -
                 Some(stream)
             }
             ServerMessage::Deny(error) => {
-                eprintln!("Server denied your connection: {}", error);
+                report_err(format!("Server denied your connection: {}", error).as_str());
                 None
             }
             _ => None
@@ -75,6 +153,31 @@ async fn try_verify_connection(addr: SocketAddr, client: &mut ClientWorkspace) -
 const DISCOVERY_PORT: u16 = 54000;
 const MAX_BUFFER_SIZE: usize = 1024;
 
+/// "jam?", asks every server on the network to introduce itself
+pub const DISCOVERY_LIST_QUERY: &str = "jam?";
+
+/// "jam!<Json>", answer to the list query
+pub const DISCOVERY_LIST_ANSWER: &str = "jam!";
+
+/// Time to wait for answers of the list query
+const DISCOVERY_LIST_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Workspace found on local network
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DiscoveredWorkspace {
+    #[serde(rename = "Name")]
+    pub name: String,
+
+    #[serde(rename = "Version")]
+    pub version: String,
+
+    #[serde(rename = "Members")]
+    pub members: usize,
+
+    #[serde(rename = "Address")]
+    pub address: String,
+}
+
 /// Discover workspace on local network
 pub async fn search_workspace_lan(workspace_name: String) -> Result<SocketAddr, Box<dyn StdError + Send + Sync>> {
     let socket = UdpSocket::bind("0.0.0.0:0").await?;
@@ -90,8 +193,41 @@ pub async fn search_workspace_lan(workspace_name: String) -> Result<SocketAddr,
     parse_socket_addr(response)
 }
 
+/// Discover all workspaces on local network, sorted by name
+pub async fn discover_workspaces_lan() -> Result<Vec<DiscoveredWorkspace>, Box<dyn StdError + Send + Sync>> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.set_broadcast(true)?;
+
+    let target_addr = format!("{}:{}", get_broadcast_address(), DISCOVERY_PORT);
+    socket.send_to(DISCOVERY_LIST_QUERY.as_bytes(), &target_addr).await?;
+
+    // Collect answers until no more servers reply
+    let mut found: Vec<DiscoveredWorkspace> = Vec::new();
+    let mut buf = [0u8; MAX_BUFFER_SIZE];
+    let deadline = sleep(DISCOVERY_LIST_TIMEOUT);
+    pin!(deadline);
+    loop {
+        let received = select! {
+            _ = &mut deadline => break,
+            received = socket.recv_from(&mut buf) => received,
+        };
+        let Ok((len, _)) = received else { continue; };
+        let Some(info) = std::str::from_utf8(&buf[..len]).ok()
+            .and_then(|answer| answer.strip_prefix(DISCOVERY_LIST_ANSWER))
+            .and_then(|json| serde_json::from_str::<DiscoveredWorkspace>(json).ok()) else {
+            continue;
+        };
+        if parse_socket_addr(&info.address).is_ok() && !found.iter().any(|known| known.address == info.address) {
+            found.push(info);
+        }
+    }
+
+    found.sort_by(|a, b| a.name.cmp(&b.name).then(a.address.cmp(&b.address)));
+    Ok(found)
+}
+
 /// Parse socket address from string
-fn parse_socket_addr(addr_str: &str) -> Result<SocketAddr, Box<dyn StdError + Send + Sync>> {
+pub fn parse_socket_addr(addr_str: &str) -> Result<SocketAddr, Box<dyn StdError + Send + Sync>> {
     let parts: Vec<&str> = addr_str.split(':').collect();
     if parts.len() != 2 {
         return Err(format!("Invalid address format: {}", addr_str).into());
@@ -107,7 +243,7 @@ fn parse_socket_addr(addr_str: &str) -> Result<SocketAddr, Box<dyn StdError + Se
 }
 
 /// Get broadcast address for current network
-fn get_broadcast_address() -> Ipv4Addr {
+pub fn get_broadcast_address() -> Ipv4Addr {
     let local_address =
         Ipv4Addr::from_str(
             local_ipaddress::get().unwrap_or("127.0.0.1".to_string()).as_str()
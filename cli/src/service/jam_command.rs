@@ -5,7 +5,7 @@ use colored::{ColoredString, Colorize};
 use log::info;
 use tokio::net::TcpStream;
 use tokio::sync::Mutex;
-use crate::data::client_result::ClientResult;
+use crate::data::client_result::{report_err, ClientResult};
 use crate::data::database::Database;
 use crate::data::member::Member;
 
@@ -32,7 +32,7 @@ pub async fn execute_local_command(
             // Execute command
             return command.local(stream, args).await;
         } else {
-            eprintln!("Unknown command: {}", command_name);
+            report_err(format!("Unknown command: {}", command_name).as_str());
         }
     }
     None
@@ -0,0 +1,460 @@
+use crate::data::server_hooks::{Hook, HookAction, HookEvent, ServerHooks};
+use crate::data::workspace::Workspace;
+use crate::service::jam_notifier::ServerEvent;
+use jam_ready::utils::local_archive::LocalArchive;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::env::current_dir;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::fs::OpenOptions;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::process::Command;
+use tokio::spawn;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::time::{sleep, timeout};
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::ServerName;
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+/// Time a single delivery may take
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Wait before the first retry, doubled after every failure
+const HOOK_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Document sent to hooks
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HookPayload {
+    #[serde(rename = "Workspace")]
+    pub workspace: String,
+
+    #[serde(rename = "Hook")]
+    pub hook: HookEvent,
+
+    /// Member whose command caused the event
+    #[serde(rename = "Member")]
+    pub member: String,
+
+    /// Unix time (seconds)
+    #[serde(rename = "Time")]
+    pub time: u64,
+
+    #[serde(rename = "Event")]
+    pub event: ServerEvent,
+}
+
+/// One delivery attempt, appended to the delivery log
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HookDelivery {
+    /// Unix time (seconds)
+    #[serde(rename = "time")]
+    pub time: u64,
+
+    #[serde(rename = "hook")]
+    pub hook: String,
+
+    #[serde(rename = "event")]
+    pub event: HookEvent,
+
+    /// 1 for the first attempt
+    #[serde(rename = "attempt")]
+    pub attempt: u32,
+
+    #[serde(rename = "success")]
+    pub success: bool,
+
+    /// HTTP status, exit code or error
+    #[serde(rename = "detail")]
+    pub detail: String,
+}
+
+/// Start a delivery worker per configured hook
+/// Returns the sender the notifier feeds (member name, event), None if no hooks are configured
+pub async fn start_hooks() -> Option<UnboundedSender<(String, ServerEvent)>> {
+    let hooks = ServerHooks::read().await.hooks;
+    if hooks.is_empty() {
+        return None;
+    }
+    info!("Hooks: {}", hooks.iter().map(|hook| hook.name.as_str()).collect::<Vec<_>>().join(", "));
+
+    // One writer keeps the lines of different hooks apart
+    let (log, log_receiver) = unbounded_channel();
+    spawn(HookLog::write(log_receiver));
+
+    let workers: Vec<(Hook, UnboundedSender<HookPayload>)> = hooks.into_iter()
+        .map(|hook| {
+            let (sender, receiver) = unbounded_channel();
+            spawn(hook_worker(hook.clone(), receiver, HOOK_RETRY_DELAY, log.clone()));
+            (hook, sender)
+        })
+        .collect();
+
+    // Hand every event to the hooks it matches
+    let (sender, mut receiver) = unbounded_channel::<(String, ServerEvent)>();
+    spawn(async move {
+        while let Some((member, event)) = receiver.recv().await {
+            let Some(hook_event) = hook_event(&event) else { continue; };
+            let paths = event.paths();
+            let payload = HookPayload {
+                workspace: Workspace::read().await.server.map(|server| server.workspace_name).unwrap_or_default(),
+                hook: hook_event,
+                member,
+                time: unix_time(),
+                event: event.clone(),
+            };
+            for (hook, worker) in &workers {
+                if hook.matches(hook_event, &paths) {
+                    let _ = worker.send(payload.clone());
+                }
+            }
+        }
+    });
+    Some(sender)
+}
+
+/// Hook event of a server event
+fn hook_event(event: &ServerEvent) -> Option<HookEvent> {
+    match event {
+        ServerEvent::FileAdded { .. } => Some(HookEvent::Add),
+        ServerEvent::FileMoved { .. } => Some(HookEvent::Move),
+        ServerEvent::FileRemoved { .. } => Some(HookEvent::Remove),
+        ServerEvent::VersionCommitted { .. } => Some(HookEvent::Commit),
+        ServerEvent::RolledBack { .. } => Some(HookEvent::Rollback),
+        ServerEvent::LockTaken { .. } => Some(HookEvent::Lock),
+        ServerEvent::LockReleased { .. } => Some(HookEvent::Unlock),
+        ServerEvent::MemberJoined { .. } => Some(HookEvent::Join),
+        ServerEvent::Archived => Some(HookEvent::Archive),
+        ServerEvent::Resync => None,
+    }
+}
+
+/// Deliver events to one hook in order, retrying failed deliveries
+/// Every attempt is sent to the log
+async fn hook_worker(
+    hook: Hook,
+    mut payloads: UnboundedReceiver<HookPayload>,
+    retry_delay: Duration,
+    log: UnboundedSender<HookDelivery>
+) {
+    while let Some(payload) = payloads.recv().await {
+        let Ok(json) = serde_json::to_string(&payload) else { continue; };
+
+        let mut delay = retry_delay;
+        for attempt in 1..=hook.retries + 1 {
+            let result = match timeout(HOOK_TIMEOUT, deliver(&hook.action, &payload, &json)).await {
+                Ok(result) => result,
+                Err(_) => Err(format!("Timed out after {}s", HOOK_TIMEOUT.as_secs())),
+            };
+            let success = result.is_ok();
+            let detail = result.unwrap_or_else(|err| err);
+            if !success {
+                warn!("Hook \"{}\" failed ({}/{}): {}", hook.name, attempt, hook.retries + 1, detail);
+            }
+
+            let _ = log.send(HookDelivery {
+                time: unix_time(),
+                hook: hook.name.clone(),
+                event: payload.hook,
+                attempt,
+                success,
+                detail,
+            });
+
+            if success { break; }
+            if attempt <= hook.retries {
+                sleep(delay).await;
+                delay *= 2;
+            }
+        }
+    }
+}
+
+/// Run the action once, returns the detail to log
+async fn deliver(action: &HookAction, payload: &HookPayload, json: &str) -> Result<String, String> {
+    match action {
+        HookAction::Command(command) => run_command(command, payload, json).await,
+        HookAction::Post(url) => post_json(url, json).await,
+    }
+}
+
+/// Run a shell command with the payload as input (and its kind in "JAM_HOOK_EVENT")
+async fn run_command(command: &str, payload: &HookPayload, json: &str) -> Result<String, String> {
+    let mut process = if cfg!(windows) {
+        let mut process = Command::new("cmd");
+        process.arg("/C").arg(command);
+        process
+    } else {
+        let mut process = Command::new("sh");
+        process.arg("-c").arg(command);
+        process
+    };
+    let mut child = process
+        .env("JAM_HOOK_EVENT", format!("{:?}", payload.hook))
+        .env("JAM_HOOK_MEMBER", &payload.member)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|err| format!("Failed to start: {}", err))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(json.as_bytes()).await;
+    }
+    let status = child.wait().await.map_err(|err| err.to_string())?;
+    if status.success() {
+        Ok("Exit code 0".to_string())
+    } else {
+        Err(match status.code() {
+            Some(code) => format!("Exit code {}", code),
+            None => "Terminated by signal".to_string(),
+        })
+    }
+}
+
+/// POST JSON to an "http://" or "https://" URL, any 2xx status is a success
+async fn post_json(url: &str, json: &str) -> Result<String, String> {
+    let (secure, rest) = match (url.strip_prefix("https://"), url.strip_prefix("http://")) {
+        (Some(rest), _) => (true, rest),
+        (_, Some(rest)) => (false, rest),
+        _ => return Err("Only http:// and https:// URLs are supported".to_string()),
+    };
+    let (host, path) = match rest.find('/') {
+        Some(index) => (&rest[..index], &rest[index..]),
+        None => (rest, "/"),
+    };
+    let host_name = host.split(':').next().unwrap_or(host);
+    let address = if host.contains(':') {
+        host.to_string()
+    } else {
+        format!("{}:{}", host, if secure { 443 } else { 80 })
+    };
+
+    let stream = TcpStream::connect(&address).await
+        .map_err(|err| format!("Failed to connect to {}: {}", address, err))?;
+    let request = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: jam/{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path, host, env!("CARGO_PKG_VERSION"), json.len(), json);
+
+    let status = if secure {
+        let server_name = ServerName::try_from(host_name.to_string())
+            .map_err(|_| format!("Invalid host name: {}", host_name))?;
+        let stream = tls_connector()?.connect(server_name, stream).await
+            .map_err(|err| format!("TLS handshake with {} failed: {}", host_name, err))?;
+        exchange(stream, &request).await?
+    } else {
+        exchange(stream, &request).await?
+    };
+    if (200..300).contains(&status) {
+        Ok(format!("HTTP {}", status))
+    } else {
+        Err(format!("HTTP {}", status))
+    }
+}
+
+/// TLS client trusting the bundled Mozilla root certificates
+fn tls_connector() -> Result<TlsConnector, String> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|err| err.to_string())?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+/// Send the request and read the status of the answer, e.g. "HTTP/1.1 204 No Content"
+async fn exchange<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, request: &str) -> Result<u16, String> {
+    stream.write_all(request.as_bytes()).await.map_err(|err| err.to_string())?;
+    stream.flush().await.map_err(|err| err.to_string())?;
+
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line).await.map_err(|err| err.to_string())?;
+    status_line.split_whitespace().nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| format!("Invalid response: {}", status_line.trim()))
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// # Hook Log - Delivery attempts of hooks, one JSON record per line
+pub struct HookLog;
+
+impl HookLog {
+    /// Delivery log path
+    pub fn path() -> Option<PathBuf> {
+        current_dir().ok().map(|current| current.join(env!("FILE_HOOK_LOG")))
+    }
+
+    /// Append the delivery attempts as they arrive
+    async fn write(mut deliveries: UnboundedReceiver<HookDelivery>) {
+        while let Some(delivery) = deliveries.recv().await {
+            Self::append(&delivery).await;
+        }
+    }
+
+    /// Append one delivery attempt
+    async fn append(delivery: &HookDelivery) {
+        let Some(path) = Self::path() else { return; };
+        let Ok(mut line) = serde_json::to_string(delivery) else { return; };
+        line.push('\n');
+
+        let file = OpenOptions::new().create(true).append(true).open(&path).await;
+        match file {
+            Ok(mut file) => {
+                if let Err(err) = file.write_all(line.as_bytes()).await {
+                    error!("Failed to write hook log: {}", err);
+                }
+            }
+            Err(err) => error!("Failed to open hook log: {}", err),
+        }
+    }
+
+    /// Latest delivery attempts, oldest first
+    pub async fn latest(count: usize) -> Vec<HookDelivery> {
+        let Some(path) = Self::path() else { return Vec::new(); };
+        let Ok(content) = tokio::fs::read_to_string(path).await else { return Vec::new(); };
+        let deliveries: Vec<HookDelivery> = content.lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        deliveries[deliveries.len().saturating_sub(count)..].to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    /// Stand-in for a hook receiver, answers one connection per status in order
+    /// Returns the URL to post to
+    async fn stand_in(statuses: Vec<u16>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        spawn(async move {
+            for status in statuses {
+                let Ok((mut stream, _)) = listener.accept().await else { return; };
+                read_request(&mut stream).await;
+                let response = format!("HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        format!("http://{}/hook", address)
+    }
+
+    /// Read the head and the body, so the answer never races the request
+    async fn read_request(stream: &mut TcpStream) {
+        let mut buffer = Vec::new();
+        let mut temp_buf = [0u8; 1024];
+        loop {
+            if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+                let head = String::from_utf8_lossy(&buffer[..end]).to_lowercase();
+                let length: usize = head.lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .and_then(|length| length.trim().parse().ok())
+                    .unwrap_or(0);
+                if buffer.len() >= end + 4 + length { return; }
+            }
+            match stream.read(&mut temp_buf).await {
+                Ok(0) | Err(_) => return,
+                Ok(read) => buffer.extend_from_slice(&temp_buf[..read]),
+            }
+        }
+    }
+
+    fn hook(events: Vec<HookEvent>, paths: Vec<&str>, action: HookAction) -> Hook {
+        Hook {
+            name: "test".to_string(),
+            events,
+            paths: paths.into_iter().map(|path| path.to_string()).collect(),
+            action,
+            retries: 3,
+        }
+    }
+
+    fn payload() -> HookPayload {
+        HookPayload {
+            workspace: "Game".to_string(),
+            hook: HookEvent::Commit,
+            member: "Alice".to_string(),
+            time: 0,
+            event: ServerEvent::VersionCommitted { path: "Builds/Game.zip".to_string(), version: 2, info: "Nightly".to_string() },
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_failed_delivery_until_success() {
+        let url = stand_in(vec![500, 204]).await;
+        let (payloads, receiver) = unbounded_channel();
+        let (log, mut deliveries) = unbounded_channel();
+        spawn(hook_worker(hook(vec![], vec![], HookAction::Post(url)), receiver, Duration::from_millis(10), log));
+
+        payloads.send(payload()).unwrap();
+        drop(payloads);
+
+        let first = deliveries.recv().await.unwrap();
+        assert_eq!((first.attempt, first.success, first.detail.as_str()), (1, false, "HTTP 500"));
+        let second = deliveries.recv().await.unwrap();
+        assert_eq!((second.attempt, second.success, second.detail.as_str()), (2, true, "HTTP 204"));
+        assert_eq!(second.event, HookEvent::Commit);
+
+        // The worker ends with its payloads, nothing was attempted again
+        assert!(deliveries.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn post_fails_on_non_success_status() {
+        let url = stand_in(vec![404, 200]).await;
+        assert_eq!(post_json(&url, "{}").await, Err("HTTP 404".to_string()));
+        assert_eq!(post_json(&url, "{}").await, Ok("HTTP 200".to_string()));
+        assert!(post_json("ftp://127.0.0.1/hook", "{}").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn post_https_requires_tls() {
+        // A plain HTTP receiver answers the handshake with text
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        spawn(async move {
+            let Ok((mut stream, _)) = listener.accept().await else { return; };
+            let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\nConnection: close\r\n\r\n").await;
+        });
+        let result = post_json(&format!("https://{}/hook", address), "{}").await;
+        assert!(result.as_ref().is_err_and(|err| err.starts_with("TLS handshake")), "{:?}", result);
+    }
+
+    #[test]
+    fn hook_matches_events_and_paths() {
+        let action = HookAction::Command("true".to_string());
+        let scenes = hook(vec![HookEvent::Lock, HookEvent::Unlock], vec!["Scenes/**"], action.clone());
+        assert!(scenes.matches(HookEvent::Lock, &["Scenes/Main.unity"]));
+        assert!(scenes.matches(HookEvent::Unlock, &["Scenes/Levels/One.unity"]));
+        assert!(!scenes.matches(HookEvent::Lock, &["Art/Hero.png"]));
+        assert!(!scenes.matches(HookEvent::Commit, &["Scenes/Main.unity"]));
+
+        // Moves match on either path, events without files ignore the patterns
+        assert!(scenes.matches(HookEvent::Lock, &["Art/Main.unity", "Scenes/Main.unity"]));
+        assert!(hook(vec![HookEvent::Archive], vec!["Scenes/**"], action.clone()).matches(HookEvent::Archive, &[]));
+
+        // No events or paths match everything
+        let every = hook(vec![], vec![], action.clone());
+        assert!(every.matches(HookEvent::Join, &[]));
+        assert!(every.matches(HookEvent::Commit, &["Builds/Game.zip"]));
+
+        let builds = hook(vec![HookEvent::Commit], vec!["Builds/*.{zip,apk}"], action);
+        assert!(builds.matches(HookEvent::Commit, &["Builds/Game.apk"]));
+        assert!(!builds.matches(HookEvent::Commit, &["Builds/Old/Game.zip"]));
+    }
+}
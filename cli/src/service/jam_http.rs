@@ -0,0 +1,447 @@
+use crate::data::database::{Database, VirtualFile};
+use crate::data::member::Member;
+use crate::data::workspace::Workspace;
+use crate::service::jam_command::{execute_remote_command, CommandRegistry};
+use crate::service::messages::ServerMessage;
+use jam_ready::utils::local_archive::LocalArchive;
+use jam_ready::utils::text_process::process_path_text;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::Mutex;
+use tokio::time::timeout;
+use tokio::{join, spawn, try_join};
+
+/// Largest request accepted (head and body)
+const MAX_REQUEST_SIZE: usize = 1024 * 1024;
+
+/// Time a client may take to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// OpenAPI description of the endpoints
+const OPENAPI: &str = include_str!("../../../docs/http_api.openapi.json");
+
+/// State shared with the TCP listener
+struct HttpContext {
+    database: Arc<Mutex<Database>>,
+    commands: Arc<CommandRegistry>,
+    sender: UnboundedSender<bool>,
+}
+
+/// Parsed HTTP request
+struct HttpRequest {
+    method: String,
+    path: String,
+
+    /// Login code from "Authorization: Bearer <LOGIN_CODE>"
+    login_code: Option<String>,
+
+    body: Vec<u8>,
+}
+
+#[derive(Serialize)]
+struct WorkspaceInfo {
+    #[serde(rename = "Name")]
+    name: String,
+
+    #[serde(rename = "Version")]
+    version: String,
+
+    #[serde(rename = "Members")]
+    members: usize,
+
+    #[serde(rename = "Files")]
+    files: usize,
+}
+
+#[derive(Serialize)]
+struct FileInfo {
+    #[serde(rename = "Path")]
+    path: String,
+
+    #[serde(rename = "Version")]
+    version: u32,
+
+    #[serde(rename = "Mergeable")]
+    mergeable: bool,
+
+    /// None if nobody holds the file
+    #[serde(rename = "Lock")]
+    lock: Option<LockInfo>,
+}
+
+#[derive(Serialize)]
+struct LockInfo {
+    /// Member name of the owner
+    #[serde(rename = "Member")]
+    member: String,
+
+    /// Long locks are kept after commits
+    #[serde(rename = "Long")]
+    long: bool,
+}
+
+#[derive(Serialize)]
+struct FileDetail {
+    #[serde(flatten)]
+    file: FileInfo,
+
+    #[serde(rename = "Versions")]
+    versions: Vec<VersionInfo>,
+}
+
+#[derive(Serialize)]
+struct VersionInfo {
+    #[serde(rename = "Version")]
+    version: u32,
+
+    #[serde(rename = "Info")]
+    info: String,
+}
+
+#[derive(Serialize)]
+struct MemberInfo {
+    #[serde(rename = "Name")]
+    name: String,
+
+    #[serde(rename = "Duties")]
+    duties: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ActionRequest {
+    /// Virtual files, or searches
+    #[serde(rename = "Paths")]
+    paths: Vec<String>,
+
+    /// Destinations of "move", one per path
+    #[serde(rename = "To", default)]
+    to: Vec<String>,
+
+    /// Target version of "rollback"
+    #[serde(rename = "Version", default)]
+    version: Option<u32>,
+
+    /// Keep the lock after commits ("lock")
+    #[serde(rename = "Long", default)]
+    long: bool,
+}
+
+#[derive(Serialize)]
+struct ActionResult {
+    #[serde(rename = "Success")]
+    success: bool,
+
+    /// Answers of the server, in order
+    #[serde(rename = "Messages")]
+    messages: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct ErrorResult {
+    #[serde(rename = "Error")]
+    error: String,
+}
+
+/// HTTP/JSON API entry point
+pub async fn jam_http_entry(
+    address: String,
+    database: Arc<Mutex<Database>>,
+    commands: Arc<CommandRegistry>,
+    sender: UnboundedSender<bool>
+) {
+    let listener = match TcpListener::bind(&address).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            error!("Failed to bind HTTP API to {}: {}", address, err);
+            return;
+        }
+    };
+    info!("HTTP API: http://{}/api", address);
+
+    let context = Arc::new(HttpContext { database, commands, sender });
+    loop {
+        if let Ok((stream, _)) = listener.accept().await {
+            spawn(process_http_connection(stream, context.clone()));
+        }
+    }
+}
+
+/// Answer one request, then close the connection
+async fn process_http_connection(mut stream: TcpStream, context: Arc<HttpContext>) {
+    let (status, body) = match timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Some(request)) => route(&request, &context).await,
+        Ok(None) => error_response(400, "Malformed request"),
+        Err(_) => error_response(408, "Request timed out"),
+    };
+
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        408 => "Request Timeout",
+        409 => "Conflict",
+        _ => "Internal Server Error",
+    };
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status, reason, body.len());
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(body.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Read the request head and body
+async fn read_request(stream: &mut TcpStream) -> Option<HttpRequest> {
+    let mut buffer = Vec::new();
+    let mut temp_buf = [0u8; 4096];
+
+    // Step1: Head
+    let head_end = loop {
+        if let Some(index) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break index;
+        }
+        if buffer.len() > MAX_REQUEST_SIZE { return None; }
+        let n = stream.read(&mut temp_buf).await.ok()?;
+        if n == 0 { return None; }
+        buffer.extend_from_slice(&temp_buf[..n]);
+    };
+    let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_uppercase();
+    let target = request_line.next()?;
+    let path = target.split('?').next().unwrap_or(target);
+
+    let mut content_length = 0;
+    let mut login_code = None;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else { continue; };
+        match name.trim().to_lowercase().as_str() {
+            "content-length" => content_length = value.trim().parse().ok()?,
+            "authorization" => login_code = value.trim().strip_prefix("Bearer ").map(|code| code.trim().to_string()),
+            _ => {}
+        }
+    }
+    if content_length > MAX_REQUEST_SIZE { return None; }
+
+    // Step2: Body
+    let mut body = buffer.split_off(head_end + 4);
+    while body.len() < content_length {
+        let n = stream.read(&mut temp_buf).await.ok()?;
+        if n == 0 { return None; }
+        body.extend_from_slice(&temp_buf[..n]);
+    }
+    body.truncate(content_length);
+
+    Some(HttpRequest { method, path: decode_url(path)?, login_code, body })
+}
+
+/// Decode "%XX" escapes of the request path
+fn decode_url(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(index + 1..index + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Find the endpoint of the request
+async fn route(request: &HttpRequest, context: &HttpContext) -> (u16, String) {
+    let path = request.path.trim_end_matches('/');
+    match (request.method.as_str(), path) {
+        ("GET", "/api/openapi.json") => (200, OPENAPI.to_string()),
+        ("GET", "/api/workspace") => json_response(&workspace_info(context).await),
+        ("GET", "/api/files") => json_response(&file_list(context, false).await),
+        ("GET", "/api/locks") => json_response(&file_list(context, true).await),
+        ("GET", "/api/members") => json_response(&member_list().await),
+        ("GET", _) if path.starts_with("/api/files/") => {
+            match file_detail(context, &path["/api/files/".len()..]).await {
+                Some(detail) => json_response(&detail),
+                None => error_response(404, "No such virtual file"),
+            }
+        }
+        ("POST", _) if path.starts_with("/api/actions/") => {
+            action(request, context, &path["/api/actions/".len()..]).await
+        }
+        _ => error_response(404, "Not found"),
+    }
+}
+
+fn json_response<T: Serialize>(value: &T) -> (u16, String) {
+    match serde_json::to_string(value) {
+        Ok(json) => (200, json),
+        Err(err) => error_response(500, &err.to_string()),
+    }
+}
+
+fn error_response(status: u16, error: &str) -> (u16, String) {
+    let json = serde_json::to_string(&ErrorResult { error: error.to_string() }).unwrap_or_default();
+    (status, json)
+}
+
+async fn workspace_info(context: &HttpContext) -> WorkspaceInfo {
+    let server = Workspace::read().await.server;
+    WorkspaceInfo {
+        name: server.as_ref().map(|server| server.workspace_name.clone()).unwrap_or_default(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        members: server.map(|server| server.members.len()).unwrap_or(0),
+        files: context.database.lock().await.files().len(),
+    }
+}
+
+/// Files sorted by path, or only the locked ones
+async fn file_list(context: &HttpContext, locked_only: bool) -> Vec<FileInfo> {
+    let database = context.database.lock().await.clone();
+    let mut result = Vec::new();
+    for file in database.files() {
+        let info = file_info(file).await;
+        if locked_only && info.lock.is_none() { continue; }
+        result.push(info);
+    }
+    result.sort_by(|a, b| a.path.cmp(&b.path));
+    result
+}
+
+async fn file_detail(context: &HttpContext, search: &str) -> Option<FileDetail> {
+    let database = context.database.lock().await.clone();
+    let file = database.search_file(process_path_text(search.to_string()))?;
+    Some(FileDetail {
+        versions: file.change_histories().into_iter()
+            .map(|(version, info)| VersionInfo { version, info })
+            .collect(),
+        file: file_info(file).await,
+    })
+}
+
+async fn file_info(file: &VirtualFile) -> FileInfo {
+    FileInfo {
+        path: file.path(),
+        version: file.version(),
+        mergeable: file.is_mergeable(),
+        lock: file.get_locker_owner().await.map(|(_, member)| LockInfo {
+            member: member.member_name,
+            long: file.is_longer_lock_unchecked(),
+        }),
+    }
+}
+
+/// Members sorted by name, login codes are never listed
+async fn member_list() -> Vec<MemberInfo> {
+    let Some(server) = Workspace::read().await.server else { return Vec::new(); };
+    let mut result: Vec<MemberInfo> = server.members.into_values()
+        .map(|member| MemberInfo {
+            name: member.member_name,
+            duties: member.member_duties.iter().map(|duty| format!("{:?}", duty)).collect(),
+        })
+        .collect();
+    result.sort_by(|a, b| a.name.cmp(&b.name));
+    result
+}
+
+/// Run a file operation as the member owning the login code
+async fn action(request: &HttpRequest, context: &HttpContext, name: &str) -> (u16, String) {
+    let Some((uuid, member)) = authenticate(request).await else {
+        return error_response(401, "Send \"Authorization: Bearer <LOGIN_CODE>\" of a member");
+    };
+    let body: ActionRequest = match serde_json::from_slice(&request.body) {
+        Ok(body) => body,
+        Err(err) => return error_response(400, &format!("Invalid body: {}", err)),
+    };
+    let args = match action_args(name, &body) {
+        Ok(args) => args,
+        Err(reason) => return error_response(400, &reason),
+    };
+
+    let replies = match run_command(context, uuid, &member, args).await {
+        Ok(replies) => replies,
+        Err(err) => return error_response(500, &err.to_string()),
+    };
+
+    // Send update notification
+    let _ = context.sender.send(true);
+
+    let success = matches!(replies.last(), Some(ServerMessage::Text(_)));
+    let messages = replies.into_iter()
+        .filter_map(|reply| match reply {
+            ServerMessage::Text(message) | ServerMessage::Deny(message) => Some(message),
+            _ => None,
+        })
+        .collect();
+    let (status, json) = json_response(&ActionResult { success, messages });
+    (if success { status } else { 409 }, json)
+}
+
+/// Member of the login code in the request
+async fn authenticate(request: &HttpRequest) -> Option<(String, Member)> {
+    let login_code = request.login_code.as_ref()?;
+    let server = Workspace::read().await.server?;
+    let uuid = server.login_code_map.get(login_code)?.clone();
+    let member = server.members.get(&uuid)?.clone();
+    Some((uuid, member))
+}
+
+/// Arguments of the "file" command for an action
+fn action_args(name: &str, request: &ActionRequest) -> Result<Vec<String>, String> {
+    if request.paths.is_empty() {
+        return Err("No paths given".to_string());
+    }
+    let operation = match name {
+        "add" | "remove" | "move" | "rollback" | "mergeable" | "unmergeable" => name,
+        "lock" if request.long => "get_longer",
+        "lock" => "get",
+        "unlock" => "throw",
+        _ => return Err(format!("Unknown action '{}'", name)),
+    };
+
+    let mut args = vec!["file".to_string(), operation.to_string(), request.paths.join("|")];
+    match name {
+        "move" if request.to.len() != request.paths.len() =>
+            return Err("\"To\" needs one destination per path".to_string()),
+        "move" => args.push(request.to.join("|")),
+        "rollback" => match request.version {
+            Some(version) => args.push(version.to_string()),
+            None => return Err("\"Version\" is missing".to_string()),
+        },
+        _ => {}
+    }
+    Ok(args)
+}
+
+/// Run a command of the registry over a loopback connection, exactly as for TCP clients
+/// Returns the messages sent by the server
+async fn run_command(context: &HttpContext, uuid: String, member: &Member, args: Vec<String>) -> io::Result<Vec<ServerMessage>> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let address = listener.local_addr()?;
+    let (mut client, (mut server, _)) = try_join!(TcpStream::connect(address), listener.accept())?;
+
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let remote = async {
+        execute_remote_command(context.commands.as_ref(), &mut server, args, (uuid, member), context.database.clone()).await;
+        let _ = server.shutdown().await;
+    };
+    let mut received = Vec::new();
+    let (_, read) = join!(remote, client.read_to_end(&mut received));
+    read?;
+
+    // Answers are JSON messages, the database sync after them is not needed
+    Ok(serde_json::Deserializer::from_slice(&received)
+        .into_iter::<ServerMessage>()
+        .map_while(Result::ok)
+        .collect())
+}
@@ -0,0 +1,318 @@
+use crate::data::database::Database;
+use crate::data::workspace::Workspace;
+use bincode::{Decode, Encode};
+use jam_ready::utils::local_archive::LocalArchive;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::broadcast::{channel, Receiver, Sender};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::Mutex;
+
+/// Events kept for slow subscribers before they miss some
+const EVENT_BUFFER_SIZE: usize = 256;
+
+/// Change on the server, pushed to subscribed clients
+#[derive(Serialize, Deserialize, Encode, Decode, PartialEq, Debug, Clone)]
+#[serde(tag = "Kind")]
+pub enum ServerEvent {
+    FileAdded {
+        #[serde(rename = "Path")]
+        path: String,
+    },
+
+    FileMoved {
+        #[serde(rename = "From")]
+        from: String,
+
+        #[serde(rename = "To")]
+        to: String,
+    },
+
+    FileRemoved {
+        #[serde(rename = "Path")]
+        path: String,
+    },
+
+    /// New version committed
+    VersionCommitted {
+        #[serde(rename = "Path")]
+        path: String,
+
+        #[serde(rename = "Version")]
+        version: u32,
+
+        /// Change info of the version
+        #[serde(rename = "Info")]
+        info: String,
+    },
+
+    /// Version set back to an older one
+    RolledBack {
+        #[serde(rename = "Path")]
+        path: String,
+
+        #[serde(rename = "Version")]
+        version: u32,
+    },
+
+    LockTaken {
+        #[serde(rename = "Path")]
+        path: String,
+
+        /// Member name of the owner
+        #[serde(rename = "Member")]
+        member: String,
+    },
+
+    LockReleased {
+        #[serde(rename = "Path")]
+        path: String,
+    },
+
+    /// First connection of a member since the server started
+    MemberJoined {
+        #[serde(rename = "Member")]
+        member: String,
+    },
+
+    /// Version histories were archived
+    Archived,
+
+    /// Events were dropped because the client read too slowly, sync the database
+    Resync,
+}
+
+impl Display for ServerEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServerEvent::FileAdded { path } => write!(f, "Added \"{}\"", path),
+            ServerEvent::FileMoved { from, to } => write!(f, "Moved \"{}\" to \"{}\"", from, to),
+            ServerEvent::FileRemoved { path } => write!(f, "Removed \"{}\"", path),
+            ServerEvent::VersionCommitted { path, version, info } if info.trim().is_empty() =>
+                write!(f, "\"{}\" is now v{}", path, version),
+            ServerEvent::VersionCommitted { path, version, info } =>
+                write!(f, "\"{}\" is now v{}: {}", path, version, info.trim()),
+            ServerEvent::RolledBack { path, version } => write!(f, "\"{}\" rolled back to v{}", path, version),
+            ServerEvent::LockTaken { path, member } => write!(f, "{} locked \"{}\"", member, path),
+            ServerEvent::LockReleased { path } => write!(f, "\"{}\" is free", path),
+            ServerEvent::MemberJoined { member } => write!(f, "{} joined", member),
+            ServerEvent::Archived => write!(f, "Version histories archived"),
+            ServerEvent::Resync => write!(f, "Missed some changes, run update"),
+        }
+    }
+}
+
+impl ServerEvent {
+    /// Virtual files the event is about
+    pub fn paths(&self) -> Vec<&str> {
+        match self {
+            ServerEvent::FileAdded { path } |
+            ServerEvent::FileRemoved { path } |
+            ServerEvent::VersionCommitted { path, .. } |
+            ServerEvent::RolledBack { path, .. } |
+            ServerEvent::LockTaken { path, .. } |
+            ServerEvent::LockReleased { path } => vec![path.as_str()],
+            ServerEvent::FileMoved { from, to } => vec![from.as_str(), to.as_str()],
+            ServerEvent::MemberJoined { .. } | ServerEvent::Archived | ServerEvent::Resync => Vec::new(),
+        }
+    }
+}
+
+/// Publishes every database write with the member whose command made it
+pub struct ServerNotifier {
+    database: Arc<Mutex<Database>>,
+
+    /// Database as last published, None while nobody is subscribed
+    published: Mutex<Option<Database>>,
+
+    /// Members connected since the server started
+    joined: Mutex<HashSet<String>>,
+
+    events: Sender<ServerEvent>,
+
+    /// Hook deliveries (member who caused the change, event), None if no hooks are configured
+    hooks: Option<UnboundedSender<(String, ServerEvent)>>,
+}
+
+/// Notifier of the running server, reached by the commands writing the database
+static NOTIFIER: OnceLock<Arc<ServerNotifier>> = OnceLock::new();
+
+impl ServerNotifier {
+    pub async fn new(database: Arc<Mutex<Database>>, hooks: Option<UnboundedSender<(String, ServerEvent)>>) -> Self {
+        let (events, _) = channel(EVENT_BUFFER_SIZE);
+
+        // Hooks always listen, so compare from the start
+        let published = if hooks.is_some() {
+            Some(database.lock().await.clone())
+        } else {
+            None
+        };
+        Self {
+            database,
+            published: Mutex::new(published),
+            joined: Mutex::new(HashSet::new()),
+            events,
+            hooks,
+        }
+    }
+
+    /// Publish the writes of all commands through this notifier
+    pub fn install(notifier: Arc<ServerNotifier>) {
+        let _ = NOTIFIER.set(notifier);
+    }
+
+    /// Receive the changes from now on
+    pub async fn subscribe(&self) -> Receiver<ServerEvent> {
+        // Database first, the same order as writes being published
+        let database = self.database.lock().await;
+        let mut published = self.published.lock().await;
+        let receiver = self.events.subscribe();
+        if published.is_none() {
+            *published = Some(database.clone());
+        }
+        receiver
+    }
+
+    /// Publish the files changed by one write (the database compared with the snapshot taken before it)
+    /// Changes of other commands are left to their own writes
+    async fn publish_changes(&self, snapshot: &Database, database: &Database, member_name: &str) {
+        let mut published = self.published.lock().await;
+
+        // Nobody listens, compare again from the next subscription
+        if self.events.receiver_count() == 0 && self.hooks.is_none() {
+            *published = None;
+            return;
+        }
+
+        let Some(previous) = published.as_mut() else { return; };
+        let mut current = previous.clone();
+        for (uuid, file) in database.changed_files(snapshot) {
+            current.put_virtual_file(uuid, file);
+        }
+        for event in file_events(previous, &current).await {
+            self.publish(member_name, event);
+        }
+        *previous = current;
+    }
+
+    /// Publish the first connection of a member
+    pub async fn member_connected(&self, uuid: &str, member_name: &str) {
+        if self.joined.lock().await.insert(uuid.to_string()) {
+            self.publish(member_name, ServerEvent::MemberJoined { member: member_name.to_string() });
+        }
+    }
+
+    fn publish(&self, member_name: &str, event: ServerEvent) {
+        if let Some(hooks) = &self.hooks {
+            let _ = hooks.send((member_name.to_string(), event.clone()));
+        }
+        let _ = self.events.send(event);
+    }
+}
+
+/// Publish the changes of a write made by a command of the member
+/// Called while the database is still locked, so writes are published in the order they were made
+pub async fn publish_write(snapshot: &Database, database: &Database, member_name: &str) {
+    if let Some(notifier) = NOTIFIER.get() {
+        notifier.publish_changes(snapshot, database, member_name).await;
+    }
+}
+
+/// Compare the database with the snapshot
+async fn file_events(snapshot: &Database, database: &Database) -> Vec<ServerEvent> {
+    let changed = database.changed_files(snapshot);
+    if changed.is_empty() {
+        return Vec::new();
+    }
+    let members = Workspace::read().await.server.map(|server| server.members).unwrap_or_default();
+
+    let mut changed: Vec<_> = changed.into_iter().collect();
+    changed.sort_by_key(|(_, file)| file.path());
+
+    let mut events = Vec::new();
+    let mut archived = false;
+    for (uuid, file) in changed {
+        let old = snapshot.file_with_uuid(uuid);
+
+        // Archiving clears the histories and nothing else
+        if old.is_some_and(|old| !old.change_histories().is_empty()) && file.change_histories().is_empty() {
+            archived = true;
+        }
+
+        let old_path = old.map(|old| old.path()).unwrap_or_default();
+        let path = file.path();
+
+        // Removed files keep their Uuid without a path
+        match (old_path.is_empty(), path.is_empty()) {
+            (true, true) => continue,
+            (true, false) => events.push(ServerEvent::FileAdded { path: path.clone() }),
+            (false, true) => {
+                events.push(ServerEvent::FileRemoved { path: old_path });
+                continue;
+            }
+            (false, false) if old_path != path => events.push(ServerEvent::FileMoved { from: old_path, to: path.clone() }),
+            _ => {}
+        }
+
+        match old.map(|old| old.version()) {
+            Some(version) if version < file.version() => {
+                let info = file.change_histories().into_iter()
+                    .find(|(version, _)| *version == file.version())
+                    .map(|(_, info)| info)
+                    .unwrap_or_default();
+                events.push(ServerEvent::VersionCommitted { path: path.clone(), version: file.version(), info });
+            }
+            Some(version) if version > file.version() =>
+                events.push(ServerEvent::RolledBack { path: path.clone(), version: file.version() }),
+            _ => {}
+        }
+
+        let old_owner = old.and_then(|old| old.get_locker_owner_uuid()).map(|owner| owner.trim().to_string());
+        let owner = file.get_locker_owner_uuid().map(|owner| owner.trim().to_string());
+        if old_owner != owner {
+            events.push(match owner {
+                Some(owner) => ServerEvent::LockTaken {
+                    path,
+                    member: members.get(&owner).map(|member| member.member_name.clone()).unwrap_or(owner),
+                },
+                None => ServerEvent::LockReleased { path },
+            });
+        }
+    }
+    if archived {
+        events.push(ServerEvent::Archived);
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::database::VirtualFile;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[tokio::test]
+    async fn overlapping_commands_publish_their_own_changes() {
+        let mut database = Database::default();
+        let _ = database.insert_virtual_file(VirtualFile::new("Art/Hero.png".to_string()));
+        let _ = database.insert_virtual_file(VirtualFile::new("Art/Tree.png".to_string()));
+
+        let (hooks, mut deliveries) = unbounded_channel();
+        let notifier = ServerNotifier::new(Arc::new(Mutex::new(database.clone())), Some(hooks)).await;
+
+        // Alice's command starts, Bob's command writes and finishes first
+        let alice_snapshot = database.clone();
+        let bob_snapshot = database.clone();
+        database.move_file("Art/Tree.png".to_string(), "Art/Oak.png".to_string()).unwrap();
+        notifier.publish_changes(&bob_snapshot, &database, "Bob").await;
+        database.move_file("Art/Hero.png".to_string(), "Art/Knight.png".to_string()).unwrap();
+        notifier.publish_changes(&alice_snapshot, &database, "Alice").await;
+
+        let moved = |from: &str, to: &str| ServerEvent::FileMoved { from: from.to_string(), to: to.to_string() };
+        assert_eq!(deliveries.recv().await, Some(("Bob".to_string(), moved("Art/Tree.png", "Art/Oak.png"))));
+        assert_eq!(deliveries.recv().await, Some(("Alice".to_string(), moved("Art/Hero.png", "Art/Knight.png"))));
+        assert!(deliveries.try_recv().is_err());
+    }
+}
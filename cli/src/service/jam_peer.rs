@@ -0,0 +1,198 @@
+use crate::data::local_cache::LocalCache;
+use crate::data::workspace::Workspace;
+use crate::service::commands::utils_file_transmitter::{read_file, send_file};
+use crate::service::jam_client::get_broadcast_address;
+use crate::service::messages::{ClientMessage, ServerMessage};
+use crate::service::service_utils::{bind_discovery_socket, get_self_address_with_port_str, read_msg, send_msg};
+use jam_ready::utils::file_digest::md5_digest;
+use jam_ready::utils::local_archive::LocalArchive;
+use std::path::{Path, PathBuf};
+use std::str::from_utf8;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::signal::ctrl_c;
+use tokio::time::{sleep, timeout};
+use tokio::{pin, select, spawn};
+
+const DISCOVERY_PORT: u16 = 54000;
+const MAX_BUFFER_SIZE: usize = 1024;
+
+/// "peer?<Workspace>|<Digest>"
+const PEER_QUERY: &str = "peer?";
+
+/// "peer!<Digest>|<Address>", the peer holds the blob
+const PEER_FOUND: &str = "peer!";
+
+/// "peer-<Digest>", the peer is running but does not hold the blob
+const PEER_MISSING: &str = "peer-";
+
+/// Time to wait for answers
+const PEER_SEARCH_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// Time to connect to a peer
+const PEER_CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Time to receive a blob from a peer, the server is used after it
+const PEER_TRANSFER_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Result of a peer download
+#[derive(PartialEq, Debug)]
+pub enum PeerFetch {
+    /// Received and verified
+    Received,
+
+    /// Peers answered, but none could deliver the blob
+    Missing,
+
+    /// No peer answered
+    NoPeers,
+}
+
+/// Peer cache entry point, shares the local cache with other clients of the workspace
+pub async fn jam_peer_entry() {
+
+    // Workspace type check
+    let Some(client) = Workspace::read().await.client else {
+        eprintln!("It's not a client workspace.");
+        return;
+    };
+
+    let address = get_self_address_with_port_str(env!("DEFAULT_PEER_PORT"));
+    let Ok(listener) = TcpListener::bind(&address).await else {
+        eprintln!("Bind listener failed");
+        return;
+    };
+    let Ok(socket) = bind_discovery_socket(DISCOVERY_PORT) else {
+        eprintln!("Bind discovery port failed");
+        return;
+    };
+    let mut buf = [0u8; MAX_BUFFER_SIZE];
+
+    println!("Workspace: \"{}\", Sharing cache on {}, press Ctrl + C to stop.", client.workspace_name, address);
+
+    loop {
+        select! {
+
+            // Ctrl + C shutdown
+            Ok(()) = ctrl_c() => {
+                println!("Good bye!");
+                break;
+            }
+
+            // Send cached blobs
+            Ok((stream, _)) = listener.accept() => {
+                spawn(serve_peer(stream));
+            }
+
+            // Answer blob queries of the same workspace
+            Ok((len, addr)) = socket.recv_from(&mut buf) => {
+                let Some(query) = from_utf8(&buf[..len]).ok().and_then(|received| received.strip_prefix(PEER_QUERY)) else {
+                    continue;
+                };
+                let Some((workspace_name, digest)) = query.split_once('|') else { continue; };
+                if workspace_name != client.workspace_name {
+                    continue;
+                }
+
+                let answer = if find_cached(digest).await.is_some() {
+                    format!("{}{}|{}", PEER_FOUND, digest, address)
+                } else {
+                    format!("{}{}", PEER_MISSING, digest)
+                };
+                let _ = socket.send_to(answer.as_bytes(), addr).await;
+            }
+        }
+    }
+}
+
+/// Find a cached file by the digest recorded when it was cached
+async fn find_cached(digest: &str) -> Option<PathBuf> {
+    if digest.is_empty() {
+        return None;
+    }
+    let directory = LocalCache::directory()?;
+    LocalCache::read().await.entries.iter()
+        .find(|(_, entry)| entry.digest == digest)
+        .map(|(key, _)| directory.join(key))
+        .filter(|path| path.is_file())
+}
+
+/// Send one cached blob
+async fn serve_peer(mut stream: TcpStream) {
+    let ClientMessage::Text(digest) = read_msg::<ClientMessage>(&mut stream).await else { return; };
+    match find_cached(&digest).await {
+        Some(path) => {
+            send_msg(&mut stream, &ServerMessage::Pass).await;
+            let _ = send_file(&mut stream, path).await;
+        }
+        None => send_msg(&mut stream, &ServerMessage::Deny("Not cached".to_string())).await,
+    }
+}
+
+/// Ask peers of the workspace for a blob, and download it from the first one that holds it
+/// The file is only written to the save path after its digest was checked
+pub async fn fetch_from_peers(workspace_name: &str, digest: &str, save_path: &Path) -> PeerFetch {
+    let Ok(socket) = UdpSocket::bind("0.0.0.0:0").await else { return PeerFetch::NoPeers; };
+    if socket.set_broadcast(true).is_err() {
+        return PeerFetch::NoPeers;
+    }
+    let target = format!("{}:{}", get_broadcast_address(), DISCOVERY_PORT);
+    let query = format!("{}{}|{}", PEER_QUERY, workspace_name, digest);
+    if socket.send_to(query.as_bytes(), &target).await.is_err() {
+        return PeerFetch::NoPeers;
+    }
+
+    // Try peers in the order they answer
+    let mut answered = false;
+    let mut buf = [0u8; MAX_BUFFER_SIZE];
+    let deadline = sleep(PEER_SEARCH_TIMEOUT);
+    pin!(deadline);
+    loop {
+        let received = select! {
+            _ = &mut deadline => break,
+            received = socket.recv_from(&mut buf) => received,
+        };
+        let Ok((len, _)) = received else { continue; };
+        let Ok(answer) = from_utf8(&buf[..len]) else { continue; };
+
+        if answer.strip_prefix(PEER_MISSING) == Some(digest) {
+            answered = true;
+            continue;
+        }
+        let Some((answer_digest, address)) = answer.strip_prefix(PEER_FOUND).and_then(|found| found.split_once('|')) else {
+            continue;
+        };
+        if answer_digest != digest {
+            continue;
+        }
+        answered = true;
+        if download_from_peer(address, digest, save_path).await {
+            return PeerFetch::Received;
+        }
+    }
+
+    if answered { PeerFetch::Missing } else { PeerFetch::NoPeers }
+}
+
+/// Download a blob into a temporary file, then move it into place if the digest matches
+async fn download_from_peer(address: &str, digest: &str, save_path: &Path) -> bool {
+    let Ok(Ok(mut stream)) = timeout(PEER_CONNECT_TIMEOUT, TcpStream::connect(address)).await else {
+        return false;
+    };
+    send_msg(&mut stream, &ClientMessage::Text(digest.to_string())).await;
+    if timeout(PEER_CONNECT_TIMEOUT, read_msg::<ServerMessage>(&mut stream)).await != Ok(ServerMessage::Pass) {
+        return false;
+    }
+
+    // A stalled peer must not hold up the download, give up and let the server send it
+    let Some(file_name) = save_path.file_name() else { return false; };
+    let temp_path = save_path.with_file_name(format!("{}.peer", file_name.to_string_lossy()));
+    let verified = matches!(timeout(PEER_TRANSFER_TIMEOUT, read_file(&mut stream, &temp_path)).await, Ok(Ok(_))) &&
+        md5_digest(temp_path.clone()).map(|received| received == digest).unwrap_or(false);
+
+    if verified && tokio::fs::rename(&temp_path, save_path).await.is_ok() {
+        return true;
+    }
+    let _ = tokio::fs::remove_file(&temp_path).await;
+    false
+}
@@ -10,29 +10,38 @@ use colored::Colorize;
 use log::{error, info};
 use log::LevelFilter::{Info};
 use sysinfo::{get_current_pid, System};
-use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::io::AsyncReadExt;
+use tokio::net::{TcpListener, TcpStream};
 use tokio::{select, spawn};
 use tokio::signal::ctrl_c;
+use tokio::sync::broadcast::error::RecvError;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use tokio::sync::Mutex;
-use tokio::time::sleep;
+use tokio::time::{interval, sleep};
 use walkdir::WalkDir;
 use jam_ready::entry_mutex_async;
 use jam_ready::utils::local_archive::LocalArchive;
 use jam_ready::utils::logger_build::logger_build;
 use jam_ready::utils::text_process::show_tree;
 use crate::data::database::Database;
+use crate::data::database_journal::DatabaseJournal;
 use crate::data::member::Member;
 use crate::data::workspace::Workspace;
 use crate::service::commands::registry;
+use crate::service::jam_client::{DiscoveredWorkspace, DISCOVERY_LIST_ANSWER, DISCOVERY_LIST_QUERY};
 use crate::service::jam_command::{execute_remote_command, CommandRegistry};
+use crate::service::jam_hooks::start_hooks;
+use crate::service::jam_http::jam_http_entry;
+use crate::service::jam_notifier::{ServerEvent, ServerNotifier};
 use crate::service::messages::ClientMessage;
 use crate::service::messages::ClientMessage::Verify;
-use crate::service::messages::ServerMessage::{Deny, Uuid};
-use crate::service::service_utils::{get_self_address_with_port_str, read_msg, send_msg};
+use crate::service::messages::ServerMessage;
+use crate::service::messages::ServerMessage::{Deny, Pass, Uuid};
+use crate::service::service_utils::{bind_discovery_socket, get_self_address_with_port_str, read_msg, send_msg};
 
 const DISCOVERY_PORT: u16 = 54000;
 const MAX_BUFFER_SIZE: usize = 1024;
+const JOURNAL_FOLD_INTERVAL: Duration = Duration::from_secs(30);
 
 /// Server entry point
 pub async fn jam_server_entry(
@@ -55,6 +64,20 @@ pub async fn jam_server_entry(
     // Build command registry
     let commands = Arc::new(registry());
 
+    // Server events pushed to subscribed clients and hooks
+    let notifier = Arc::new(ServerNotifier::new(Arc::clone(&database), start_hooks().await).await);
+    ServerNotifier::install(Arc::clone(&notifier));
+
+    // HTTP/JSON API, sharing the commands with the TCP listener
+    if let Some(server) = workspace.server.as_ref().filter(|server| server.enable_http_api) {
+        spawn(jam_http_entry(
+            server.http_address.clone(),
+            Arc::clone(&database),
+            Arc::clone(&commands),
+            sender.clone()
+        ));
+    }
+
     // Get local IP address
     let address_tcp = get_self_address_with_port_str(env!("DEFAULT_SERVER_PORT"));
 
@@ -74,7 +97,7 @@ pub async fn jam_server_entry(
     }
 
     // Initialize network discovery
-    let socket = UdpSocket::bind(format!("0.0.0.0:{}", DISCOVERY_PORT)).await.unwrap();
+    let socket = bind_discovery_socket(DISCOVERY_PORT).unwrap();
     let mut buf = [0u8; MAX_BUFFER_SIZE];
 
     // Print server info
@@ -85,18 +108,33 @@ pub async fn jam_server_entry(
         commands.len()
     );
 
+    // Journal folding timer
+    let mut fold_timer = interval(JOURNAL_FOLD_INTERVAL);
+
     // Main event loop
     loop {
         select! {
             // Ctrl+C shutdown
             Ok(()) = ctrl_c() => {
                 info!("Shutting down");
+                entry_mutex_async!(database, |guard| {
+                    DatabaseJournal::fold(guard).await;
+                });
                 break;
             }
 
+            // Fold database journal into the main database
+            _ = fold_timer.tick() => {
+                if !DatabaseJournal::is_empty().await {
+                    entry_mutex_async!(database, |guard| {
+                        DatabaseJournal::fold(guard).await;
+                    });
+                }
+            }
+
             // Handle incoming connections
             Ok((stream, _)) = listener.accept() => {
-                spawn(process_connection(stream, Arc::clone(&database), Arc::clone(&commands), sender.clone()));
+                spawn(process_connection(stream, Arc::clone(&database), Arc::clone(&commands), Arc::clone(&notifier), sender.clone()));
             }
 
             // Network discovery
@@ -104,6 +142,17 @@ pub async fn jam_server_entry(
                 if let Ok(received) = from_utf8(&buf[..len]) {
                     if received == workspace_name {
                         let _ = socket.send_to(address_tcp.as_bytes(), addr).await;
+                    } else if received == DISCOVERY_LIST_QUERY {
+                        let info = DiscoveredWorkspace {
+                            name: workspace_name.to_string(),
+                            version: env!("CARGO_PKG_VERSION").to_string(),
+                            members: Workspace::read().await.server.map(|server| server.members.len()).unwrap_or(0),
+                            address: address_tcp.clone(),
+                        };
+                        if let Ok(json) = serde_json::to_string(&info) {
+                            let answer = format!("{}{}", DISCOVERY_LIST_ANSWER, json);
+                            let _ = socket.send_to(answer.as_bytes(), addr).await;
+                        }
                     }
                 }
             }
@@ -118,6 +167,7 @@ async fn process_connection (
     mut stream: TcpStream,
     database_arc: Arc<Mutex<Database>>,
     command_registry: Arc<CommandRegistry>,
+    notifier: Arc<ServerNotifier>,
     sender: UnboundedSender<bool>) {
 
     // Read message from client
@@ -134,16 +184,20 @@ async fn process_connection (
 
                     let member = server.members.get(uuid);
                     if let Some(member) = member {
-                        // Process member commands
-                        process_member_command(
+                        notifier.member_connected(uuid, &member.member_name).await;
+
+                        // Process member commands until the client disconnects (shell sessions send several)
+                        while process_member_command(
                             &mut stream,
                             database_arc.clone(),
                             command_registry.clone(),
+                            &notifier,
                             (uuid.clone(), member)
-                        ).await;
+                        ).await {
 
-                        // Send update notification
-                        let _ = sender.send(true);
+                            // Send update notification
+                            let _ = sender.send(true);
+                        }
                     }
                 } else {
                     send_msg(&mut stream, &Deny("Who are you?".to_string())).await;
@@ -161,17 +215,55 @@ async fn process_connection (
 }
 
 /// Process member commands
+/// Returns false when the connection is closed
 async fn process_member_command (
     stream: &mut TcpStream,
     database: Arc<Mutex<Database>>,
     command_registry: Arc<CommandRegistry>,
+    notifier: &ServerNotifier,
     (uuid, member): (String, &Member)
-) {
-    let command: ClientMessage = read_msg(stream).await;
+) -> bool {
+    loop {
+        match read_msg(stream).await {
+            ClientMessage::Command(args_input) => {
+                let args: Vec<&str> = args_input.iter().map(String::as_str).collect();
+                execute_remote_command(command_registry.as_ref(), stream, args, (uuid, member), database.clone()).await;
+                return true;
+            }
 
-    if let ClientMessage::Command(args_input) = command {
-        let args: Vec<&str> = args_input.iter().map(String::as_str).collect();
-        execute_remote_command(command_registry.as_ref(), stream, args, (uuid, member), database.clone()).await;
+            // The connection only receives events from now on
+            ClientMessage::Subscribe => {
+                serve_events(stream, notifier).await;
+                return false;
+            }
+
+            // Connection closed
+            ClientMessage::Unknown => return false,
+
+            // Leftover of the previous command
+            _ => continue,
+        }
+    }
+}
+
+/// Push server events until the client closes the connection
+async fn serve_events(stream: &mut TcpStream, notifier: &ServerNotifier) {
+    let mut events = notifier.subscribe().await;
+    send_msg(stream, &Pass).await;
+
+    let mut buf = [0u8; 1];
+    loop {
+        let event = select! {
+            event = events.recv() => event,
+
+            // Subscribed clients send nothing, so any read ends the subscription
+            _ = stream.read(&mut buf) => break,
+        };
+        match event {
+            Ok(event) => send_msg(stream, &ServerMessage::Event(event)).await,
+            Err(RecvError::Lagged(_)) => send_msg(stream, &ServerMessage::Event(ServerEvent::Resync)).await,
+            Err(RecvError::Closed) => break,
+        }
     }
 }
 
@@ -1,4 +1,5 @@
 use crate::data::database::Database;
+use crate::service::jam_notifier::ServerEvent;
 use bincode::{Decode, Encode};
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
@@ -27,10 +28,16 @@ pub enum ClientMessage {
     /// Indicate not ready status
     NotReady,
 
+    /// Abandon the operation in progress
+    Cancel,
+
     // ------ Command Operations ------
 
     /// Send command with arguments
-    Command(Vec<String>)
+    Command(Vec<String>),
+
+    /// Receive server events on this connection until it is closed
+    Subscribe
 }
 
 #[derive(Default, Serialize, Deserialize, Encode, Decode, PartialEq, Debug, Clone)]
@@ -58,5 +65,11 @@ pub enum ServerMessage {
     Text(String),
 
     /// UUID response
-    Uuid(String)
+    Uuid(String),
+
+    /// Merge conflicts (Path, Base version, Latest version, Latest real file)
+    Conflict(Vec<(String, u32, u32, String)>),
+
+    /// Change pushed to a subscribed connection
+    Event(ServerEvent)
 }
\ No newline at end of file
@@ -1,6 +1,10 @@
 pub mod jam_server;
+pub mod jam_http;
 pub mod jam_client;
+pub mod jam_peer;
 pub mod jam_command;
+pub mod jam_notifier;
+pub mod jam_hooks;
 pub mod commands;
 mod messages;
 
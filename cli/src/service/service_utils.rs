@@ -4,8 +4,10 @@ use indicatif::{ProgressBar, ProgressStyle};
 use log::{error, trace, warn};
 use serde::{Deserialize, Serialize};
 use serde::de::DeserializeOwned;
+use socket2::{Domain, Protocol, Socket, Type};
+use std::net::SocketAddr;
 use tokio::io;
-use tokio::net::TcpStream;
+use tokio::net::{TcpStream, UdpSocket};
 use tokio::io::AsyncReadExt;
 use tokio::io::AsyncWriteExt;
 
@@ -70,6 +72,45 @@ where
     Message::default()
 }
 
+/// Reads a stream of JSON messages, which may arrive in the same packet
+#[derive(Default)]
+pub struct MessageReader {
+    buffer: Vec<u8>,
+}
+
+impl MessageReader {
+    /// Next message, None once the connection is closed or broken
+    pub async fn next<Message>(&mut self, stream: &mut TcpStream) -> Option<Message>
+    where
+        Message: DeserializeOwned + Debug
+    {
+        let mut temp_buf = [0u8; 1024];
+        loop {
+            // Take a complete message from the buffer
+            let mut messages = serde_json::Deserializer::from_slice(&self.buffer).into_iter::<Message>();
+            match messages.next() {
+                Some(Ok(decoded)) => {
+                    let offset = messages.byte_offset();
+                    self.buffer.drain(..offset);
+                    trace!("Received JSON {:?} from {}", decoded, get_target_address(stream));
+                    return Some(decoded);
+                }
+                Some(Err(err)) if !err.is_eof() => {
+                    error!("Failed to deserialize JSON message: {}", err);
+                    return None;
+                }
+                _ => {}
+            }
+
+            // Incomplete, read more
+            match stream.read(&mut temp_buf).await {
+                Ok(n) if n > 0 => self.buffer.extend_from_slice(&temp_buf[..n]),
+                _ => return None,
+            }
+        }
+    }
+}
+
 /// Send large message with progress tracking
 pub async fn send_large_msg<Message>(
     stream: &mut TcpStream,
@@ -272,4 +313,14 @@ pub fn get_self_address_with_port_str(port: &str) -> String {
     local_ipaddress::get()
         .map(|ip| format!("{}:{}", ip, port))
         .unwrap_or_else(|| format!("127.0.0.1:{}", port))
+}
+
+/// Bind the discovery port, shared by the server and peer caches running on the same machine
+pub fn bind_discovery_socket(port: u16) -> std::io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.set_broadcast(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&SocketAddr::from(([0, 0, 0, 0], port)).into())?;
+    UdpSocket::from_std(socket.into())
 }
\ No newline at end of file
@@ -1,13 +1,46 @@
+use std::any::Any;
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Mutex;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use tokio::fs;
 use tokio::io::AsyncReadExt;
 use std::env::current_dir;
 
+/// Archives kept in memory (Path, Data), None while the cache is disabled
+static ARCHIVE_CACHE: Mutex<Option<HashMap<String, Box<dyn Any + Send>>>> = Mutex::new(None);
+
+/// Keep archives in memory after the first read, for long-running processes that own the workspace
+pub fn enable_archive_cache() {
+    if let Ok(mut cache) = ARCHIVE_CACHE.lock() {
+        cache.get_or_insert_with(HashMap::new);
+    }
+}
+
+/// Forget all archives kept in memory, the next read loads them from disk again
+pub fn clear_archive_cache() {
+    if let Ok(mut cache) = ARCHIVE_CACHE.lock()
+        && let Some(cache) = cache.as_mut() {
+        cache.clear();
+    }
+}
+
+fn cached_archive<T: Clone + 'static>(path: &str) -> Option<T> {
+    let cache = ARCHIVE_CACHE.lock().ok()?;
+    cache.as_ref()?.get(path)?.downcast_ref::<T>().cloned()
+}
+
+fn cache_archive<T: Clone + Send + 'static>(path: &str, val: &T) {
+    if let Ok(mut cache) = ARCHIVE_CACHE.lock()
+        && let Some(cache) = cache.as_mut() {
+        cache.insert(path.to_string(), Box::new(val.clone()));
+    }
+}
+
 #[async_trait]
 pub trait LocalArchive: Serialize + for<'a> Deserialize<'a> + Default {
-    type DataType: Serialize + for<'a> Deserialize<'a> + Default + Send + Sync;
+    type DataType: Serialize + for<'a> Deserialize<'a> + Default + Clone + Send + Sync + 'static;
 
     fn relative_path() -> String;
 
@@ -22,10 +55,15 @@ pub trait LocalArchive: Serialize + for<'a> Deserialize<'a> + Default {
     where
         Self: Sized + Send + Sync,
     {
+        // Kept in memory
+        if let Some(cached) = cached_archive::<Self::DataType>(&path) {
+            return cached;
+        }
+
         let file_path = current_dir().unwrap().join(&path);
 
         // Check if file exists
-        match fs::metadata(&file_path).await {
+        let data = match fs::metadata(&file_path).await {
             Ok(_) => {
                 // Open file
                 let mut file = fs::File::open(&file_path).await.unwrap();
@@ -44,7 +82,10 @@ pub trait LocalArchive: Serialize + for<'a> Deserialize<'a> + Default {
                 // Return default value when file doesn't exist
                 Self::DataType::default()
             }
-        }
+        };
+
+        cache_archive(&path, &data);
+        data
     }
 
     async fn update(val: &Self::DataType)
@@ -61,6 +102,8 @@ pub trait LocalArchive: Serialize + for<'a> Deserialize<'a> + Default {
         // Ensure directory exists
         create_paths().await;
 
+        cache_archive(&path, val);
+
         let file_path = current_dir().unwrap().join(&path);
 
         let mut pretty_config = ron::ser::PrettyConfig::new();